@@ -0,0 +1,65 @@
+<?php
+/**
+ * This file is auto-generated by BDE (Boring Database Engine) v0.1.0.
+ * Do not edit this file directly.
+ *
+ * To regenerate this file, run:
+ * bde generate
+ *
+ * @generated
+ */
+
+
+namespace VendorName\Testing;
+
+
+class GetUserWithProvidersRow
+{
+    
+    /**
+     * @var int
+     */
+    public int $id;
+    
+    /**
+     * @var string
+     */
+    public string $username;
+    
+    /**
+     * @var ?string
+     */
+    public ?string $provider;
+    
+    /**
+     * @var ?string
+     */
+    public ?string $provider_user_id;
+    
+    /**
+     * @param array<string, mixed> $row
+     */
+    public static function fromRow(array $row): self
+    {
+        $entity = new self();
+        $entity->id = (int) $row['id'];
+        $entity->username = $row['username'];
+        $entity->provider = $row['provider'] === null ? null : $row['provider'];
+        $entity->provider_user_id = $row['provider_user_id'] === null ? null : $row['provider_user_id'];
+
+        return $entity;
+    }
+
+    /**
+     * @return array<string, mixed>
+     */
+    public function toArray(): array
+    {
+        return [
+            'id' => $this->id,
+            'username' => $this->username,
+            'provider' => $this->provider,
+            'provider_user_id' => $this->provider_user_id,
+        ];
+    }
+}
\ No newline at end of file
@@ -13,6 +13,8 @@
 namespace VendorName\Testing;
 
 
+use DateTimeImmutable;
+
 class Oauth
 {
     
@@ -37,8 +39,36 @@ class Oauth
     public string $provider_user_id;
     
     /**
-     * @var DateTime
+     * @var DateTimeImmutable
      */
-    public DateTime $created_at;
+    public DateTimeImmutable $created_at;
     
+    /**
+     * @param array<string, mixed> $row
+     */
+    public static function fromRow(array $row): self
+    {
+        $entity = new self();
+        $entity->id = (int) $row['id'];
+        $entity->user_id = $row['user_id'] === null ? null : (int) $row['user_id'];
+        $entity->provider = $row['provider'];
+        $entity->provider_user_id = $row['provider_user_id'];
+        $entity->created_at = new DateTimeImmutable($row['created_at']);
+
+        return $entity;
+    }
+
+    /**
+     * @return array<string, mixed>
+     */
+    public function toArray(): array
+    {
+        return [
+            'id' => $this->id,
+            'user_id' => $this->user_id,
+            'provider' => $this->provider,
+            'provider_user_id' => $this->provider_user_id,
+            'created_at' => $this->created_at->format('Y-m-d H:i:s.u'),
+        ];
+    }
 }
\ No newline at end of file
@@ -0,0 +1,118 @@
+<?php
+/**
+ * This file is auto-generated by BDE (Boring Database Engine) v0.1.0.
+ * Do not edit this file directly.
+ *
+ * To regenerate this file, run:
+ * bde generate
+ *
+ * @generated
+ */
+
+
+namespace VendorName\Testing;
+
+
+use PDO;
+
+class Queries
+{
+    /**
+     * @var PDO
+     */
+    private PDO $pdo;
+
+    public function __construct(PDO $pdo)
+    {
+        $this->pdo = $pdo;
+    }
+
+    /**
+     * GetUserByID :one
+     *
+     * @param int $id
+     * @return Users|null
+     */
+    public function getUserByID(int $id): ?Users
+    {
+        $stmt = $this->pdo->prepare('SELECT * FROM users WHERE id = ?');
+        $stmt->bindValue(1, $id, PDO::PARAM_INT);
+        $stmt->execute();
+        $row = $stmt->fetch(PDO::FETCH_ASSOC);
+
+        if ($row === false) {
+            return null;
+        }
+
+        return Users::fromRow($row);
+    }
+
+    /**
+     * DeleteUser :exec
+     *
+     * @param int $id
+     * @return void
+     */
+    public function deleteUser(int $id): void
+    {
+        $stmt = $this->pdo->prepare('DELETE FROM users WHERE id = ?');
+        $stmt->bindValue(1, $id, PDO::PARAM_INT);
+        $stmt->execute();
+    }
+
+    /**
+     * GetUserWithProviders :many
+     *
+     * @param int $id
+     * @return GetUserWithProvidersRow[]
+     */
+    public function getUserWithProviders(int $id): array
+    {
+        $stmt = $this->pdo->prepare('SELECT u.id, u.username, o.provider, o.provider_user_id FROM users u LEFT JOIN oauth o ON o.user_id = u.id WHERE u.id = ?');
+        $stmt->bindValue(1, $id, PDO::PARAM_INT);
+        $stmt->execute();
+        $result = [];
+
+        while (($row = $stmt->fetch(PDO::FETCH_ASSOC)) !== false) {
+            $result[] = GetUserWithProvidersRow::fromRow($row);
+        }
+
+        return $result;
+    }
+
+    /**
+     * CreateUser :execlastid
+     *
+     * @param string $username
+     * @param string $email
+     * @param ?string $name
+     * @return int
+     */
+    public function createUser(string $username, string $email, ?string $name): int
+    {
+        $stmt = $this->pdo->prepare('INSERT INTO users (username, email, name) VALUES (?, ?, ?)');
+        $stmt->bindValue(1, $username, PDO::PARAM_STR);
+        $stmt->bindValue(2, $email, PDO::PARAM_STR);
+        $stmt->bindValue(3, $name, PDO::PARAM_STR);
+        $stmt->execute();
+
+        return (int) $this->pdo->lastInsertId();
+    }
+
+    /**
+     * RenameUsers :batchexec
+     *
+     * @param list<array{name: ?string, id: int}> $batch
+     * @return void
+     */
+    public function renameUsers(array $batch): void
+    {
+        $stmt = $this->pdo->prepare('UPDATE users SET name = ? WHERE id = ?');
+
+        foreach ($batch as $params) {
+            $stmt->bindValue(1, $params['name'], PDO::PARAM_STR);
+            $stmt->bindValue(2, $params['id'], PDO::PARAM_INT);
+            $stmt->execute();
+        }
+    }
+}
\ No newline at end of file
@@ -13,6 +13,8 @@
 namespace VendorName\Testing;
 
 
+use DateTimeImmutable;
+
 class Users
 {
     
@@ -32,13 +34,41 @@ class Users
     public string $email;
     
     /**
-     * @var DateTime
+     * @var DateTimeImmutable
      */
-    public DateTime $created_at;
+    public DateTimeImmutable $created_at;
     
     /**
      * @var ?string
      */
     public ?string $name;
     
+    /**
+     * @param array<string, mixed> $row
+     */
+    public static function fromRow(array $row): self
+    {
+        $entity = new self();
+        $entity->id = (int) $row['id'];
+        $entity->username = $row['username'];
+        $entity->email = $row['email'];
+        $entity->created_at = new DateTimeImmutable($row['created_at']);
+        $entity->name = $row['name'] === null ? null : $row['name'];
+
+        return $entity;
+    }
+
+    /**
+     * @return array<string, mixed>
+     */
+    public function toArray(): array
+    {
+        return [
+            'id' => $this->id,
+            'username' => $this->username,
+            'email' => $this->email,
+            'created_at' => $this->created_at->format('Y-m-d H:i:s.u'),
+            'name' => $this->name,
+        ];
+    }
 }
\ No newline at end of file
@@ -6,4 +6,19 @@ WHERE id = :id;
 -- name: DeleteUser :exec
 DELETE
 FROM users
-WHERE id = :id;
\ No newline at end of file
+WHERE id = :id;
+
+-- name: GetUserWithProviders :many
+SELECT u.id, u.username, o.provider, o.provider_user_id
+FROM users u
+         LEFT JOIN oauth o ON o.user_id = u.id
+WHERE u.id = :id;
+
+-- name: CreateUser :execlastid
+INSERT INTO users (username, email, name)
+VALUES (:username, :email, :name);
+
+-- name: RenameUsers :batchexec
+UPDATE users
+SET name = :name
+WHERE id = :id;
@@ -0,0 +1,336 @@
+use crate::analyzer::types::{
+    CheckConstraint, EngineData, ForeignKey, Generated, Index, Table, UniqueConstraint,
+};
+use crate::analyzer::{SchemaAnalyzer, object_name_last};
+use crate::configuration::Engine;
+use crate::diagnostics::Diagnostic;
+use anyhow::Result;
+use sqlparser::ast::*;
+use sqlparser::tokenizer::Span;
+
+impl SchemaAnalyzer {
+    /// Records the keys and constraints declared inline on a column of the table at
+    /// `table_index`, e.g. `PRIMARY KEY`, `UNIQUE`, `REFERENCES` and `CHECK`
+    pub(crate) fn add_column_constraints(
+        engine_data: &mut EngineData,
+        table_index: usize,
+        column_def: &ColumnDef,
+    ) -> Result<()> {
+        let columns = vec![column_def.name.clone()];
+
+        for option in &column_def.options {
+            let name = option.name.as_ref().map(|name| name.value.clone());
+            let table = &mut engine_data.tables[table_index];
+            match &option.option {
+                ColumnOption::Unique {
+                    is_primary: true, ..
+                } => Self::set_primary_key(table, &columns)?,
+                ColumnOption::Unique {
+                    is_primary: false, ..
+                } => table.unique_constraints.push(UniqueConstraint {
+                    name,
+                    columns: vec![column_def.name.value.clone()],
+                }),
+                ColumnOption::ForeignKey {
+                    foreign_table,
+                    referred_columns,
+                    on_delete,
+                    on_update,
+                    ..
+                } => {
+                    let foreign_key = Self::foreign_key(
+                        engine_data,
+                        name,
+                        &columns,
+                        foreign_table,
+                        referred_columns,
+                    )?;
+                    engine_data.tables[table_index]
+                        .foreign_keys
+                        .push(ForeignKey {
+                            on_delete: *on_delete,
+                            on_update: *on_update,
+                            ..foreign_key
+                        });
+                }
+                ColumnOption::Check(expr) => table.checks.push(CheckConstraint {
+                    name,
+                    expr: expr.to_string(),
+                }),
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds a table level constraint such as `PRIMARY KEY (a, b)` or MySQL's `KEY idx (a)`
+    pub(crate) fn add_table_constraint(
+        engine_data: &mut EngineData,
+        table_index: usize,
+        constraint: TableConstraint,
+        without_rowid: bool,
+    ) -> Result<()> {
+        let engine = engine_data.engine;
+
+        match constraint {
+            TableConstraint::PrimaryKey { columns, .. } => {
+                let table = &mut engine_data.tables[table_index];
+                Self::set_primary_key(table, &columns)?;
+                Self::apply_table_primary_key(table, engine, without_rowid);
+            }
+            // MySQL names the index of `UNIQUE KEY uq (a)` rather than the constraint
+            TableConstraint::Unique {
+                name,
+                index_name,
+                columns,
+                ..
+            } => {
+                let table = &mut engine_data.tables[table_index];
+                let columns = Self::constraint_columns(table, &columns)?;
+                table.unique_constraints.push(UniqueConstraint {
+                    name: name.or(index_name).map(|name| name.value),
+                    columns,
+                });
+            }
+            TableConstraint::ForeignKey {
+                name,
+                columns,
+                foreign_table,
+                referred_columns,
+                on_delete,
+                on_update,
+                ..
+            } => {
+                Self::constraint_columns(&engine_data.tables[table_index], &columns)?;
+                let foreign_key = Self::foreign_key(
+                    engine_data,
+                    name.map(|name| name.value),
+                    &columns,
+                    &foreign_table,
+                    &referred_columns,
+                )?;
+                engine_data.tables[table_index]
+                    .foreign_keys
+                    .push(ForeignKey {
+                        on_delete,
+                        on_update,
+                        ..foreign_key
+                    });
+            }
+            TableConstraint::Check { name, expr } => {
+                engine_data.tables[table_index]
+                    .checks
+                    .push(CheckConstraint {
+                        name: name.map(|name| name.value),
+                        expr: expr.to_string(),
+                    });
+            }
+            TableConstraint::Index { name, columns, .. }
+            | TableConstraint::FulltextOrSpatial {
+                opt_index_name: name,
+                columns,
+                ..
+            } => {
+                let table = &mut engine_data.tables[table_index];
+                let columns = Self::constraint_columns(table, &columns)?;
+                table.indexes.push(Index {
+                    name: name.map(|name| name.value),
+                    columns,
+                    unique: false,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Records a `CREATE [UNIQUE] INDEX` on its table. Indexed expressions are kept as SQL.
+    pub(crate) fn create_index(
+        engine_data: &mut EngineData,
+        create_index: CreateIndex,
+    ) -> Result<()> {
+        let table_name = object_name_last(&create_index.table_name);
+        let table_index = engine_data.table_index(&table_name).ok_or_else(|| {
+            Diagnostic::at_span(
+                format!("Create index references unknown table: {}", table_name),
+                create_index.table_name.span(),
+            )
+        })?;
+
+        let name = create_index.name.as_ref().map(object_name_last);
+        if let (Some(object_name), Some(name)) = (&create_index.name, &name) {
+            // MySQL names indexes per table, PostgreSQL and SQLite per schema
+            let exists = engine_data
+                .tables
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| engine_data.engine != Engine::MySql || *index == table_index)
+                .any(|(_, table)| table.has_index(name));
+            if exists && create_index.if_not_exists {
+                return Ok(());
+            }
+            if exists {
+                return Err(Diagnostic::at_span(
+                    format!("index {} already exists", name),
+                    object_name.span(),
+                )
+                .into());
+            }
+        }
+        let table = &mut engine_data.tables[table_index];
+
+        let mut columns = vec![];
+        for column in &create_index.columns {
+            match &column.expr {
+                Expr::Identifier(ident) => columns.extend(Self::constraint_columns(
+                    table,
+                    std::slice::from_ref(ident),
+                )?),
+                expr => columns.push(expr.to_string()),
+            }
+        }
+
+        table.indexes.push(Index {
+            name,
+            columns,
+            unique: create_index.unique,
+        });
+        Ok(())
+    }
+
+    /// Removes the index called `name` from whichever table it is on
+    pub(crate) fn drop_index(
+        engine_data: &mut EngineData,
+        name: &ObjectName,
+        if_exists: bool,
+    ) -> Result<()> {
+        let index_name = object_name_last(name);
+        let dropped = engine_data
+            .tables
+            .iter_mut()
+            .any(|table| table.drop_index(&index_name));
+        if !dropped && !if_exists {
+            return Err(Diagnostic::at_span(
+                format!("Drop index references unknown index: {}", index_name),
+                name.span(),
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Builds a foreign key, checking the referenced table and columns exist. Without
+    /// referenced columns the key points at the primary key of the referenced table.
+    fn foreign_key(
+        engine_data: &EngineData,
+        name: Option<String>,
+        columns: &[Ident],
+        foreign_table: &ObjectName,
+        referred_columns: &[Ident],
+    ) -> Result<ForeignKey> {
+        let referenced = engine_data
+            .find_table(&object_name_last(foreign_table))
+            .ok_or_else(|| {
+                Diagnostic::at_span(
+                    format!("foreign key references unknown table {}", foreign_table),
+                    foreign_table.span(),
+                )
+            })?;
+
+        let referenced_columns = if referred_columns.is_empty() {
+            if referenced.primary_key.is_empty() {
+                return Err(Diagnostic::at_span(
+                    format!(
+                        "foreign key references {}, which has no primary key",
+                        referenced.name
+                    ),
+                    foreign_table.span(),
+                )
+                .into());
+            }
+            referenced.primary_key.clone()
+        } else {
+            Self::constraint_columns(referenced, referred_columns)?
+        };
+
+        if referenced_columns.len() != columns.len() {
+            return Err(Diagnostic::at_span(
+                format!(
+                    "foreign key has {} columns but references {}",
+                    columns.len(),
+                    referenced_columns.len()
+                ),
+                foreign_table.span(),
+            )
+            .into());
+        }
+
+        Ok(ForeignKey {
+            name,
+            columns: columns.iter().map(|column| column.value.clone()).collect(),
+            referenced_table: referenced.name.clone(),
+            referenced_columns,
+            on_delete: None,
+            on_update: None,
+        })
+    }
+
+    /// Resolves the columns a constraint names, each has to be a column of `table`
+    fn constraint_columns(table: &Table, columns: &[Ident]) -> Result<Vec<String>> {
+        columns
+            .iter()
+            .map(|column| match table.find_column(&column.value) {
+                Some(found) => Ok(found.name.clone()),
+                None => Err(Diagnostic::at_span(
+                    format!("table {} has no column {}", table.name, column),
+                    column.span,
+                )
+                .into()),
+            })
+            .collect()
+    }
+
+    fn set_primary_key(table: &mut Table, columns: &[Ident]) -> Result<()> {
+        let columns_spans = columns.iter().map(|column| column.span);
+        let columns = Self::constraint_columns(table, columns)?;
+        let same = table.primary_key.len() == columns.len()
+            && table
+                .primary_key
+                .iter()
+                .zip(&columns)
+                .all(|(a, b)| a.eq_ignore_ascii_case(b));
+        if !table.primary_key.is_empty() && !same {
+            return Err(Diagnostic::at_span(
+                format!(
+                    "table {} already has a primary key ({})",
+                    table.name,
+                    table.primary_key.join(", ")
+                ),
+                Span::union_iter(columns_spans),
+            )
+            .into());
+        }
+        table.primary_key = columns;
+        Ok(())
+    }
+
+    /// Makes the columns of a table level primary key NOT NULL, following the same SQLite
+    /// rules as a column level one, where a rowid alias is also generated
+    pub(crate) fn apply_table_primary_key(table: &mut Table, engine: Engine, without_rowid: bool) {
+        for name in table.primary_key.clone() {
+            let Some(index) = table.column_index(&name) else {
+                continue;
+            };
+            let column = &mut table.columns[index];
+            let is_rowid_alias = table.primary_key.len() == 1
+                && matches!(column.data_type.sql_type, DataType::Integer(None));
+            if engine != Engine::Sqlite || is_rowid_alias || without_rowid {
+                column.set_nullable(false);
+            }
+            if engine == Engine::Sqlite && is_rowid_alias && !without_rowid {
+                column.generated.get_or_insert(Generated::AutoIncrement);
+            }
+        }
+    }
+}
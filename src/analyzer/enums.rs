@@ -0,0 +1,183 @@
+use crate::analyzer::types::{Column, EngineData, EnumType};
+use crate::analyzer::{PhpDataType, PhpType, SchemaAnalyzer, object_name_last, php_class_name};
+use crate::diagnostics::Diagnostic;
+use anyhow::Result;
+use sqlparser::ast::*;
+
+impl SchemaAnalyzer {
+    /// Records a `CREATE TYPE name AS ENUM (...)`, other user defined types are skipped
+    pub(crate) fn create_type(
+        engine_data: &mut EngineData,
+        name: &ObjectName,
+        representation: UserDefinedTypeRepresentation,
+    ) -> Result<()> {
+        let UserDefinedTypeRepresentation::Enum { labels } = representation else {
+            println!("Unhandled user defined type: {}", name);
+            return Ok(());
+        };
+
+        let type_name = object_name_last(name);
+        if engine_data.enum_index(&type_name).is_some() {
+            return Err(Diagnostic::at_span(
+                format!("type {} already exists", type_name),
+                name.span(),
+            )
+            .into());
+        }
+
+        engine_data.enums.push(EnumType {
+            name: type_name,
+            values: labels.into_iter().map(|label| label.value).collect(),
+        });
+        Ok(())
+    }
+
+    /// Applies `ALTER TYPE ... ADD VALUE`, `RENAME VALUE` and `RENAME TO` to an enum type
+    pub(crate) fn alter_type(engine_data: &mut EngineData, alter_type: AlterType) -> Result<()> {
+        let type_name = object_name_last(&alter_type.name);
+        let index = engine_data.enum_index(&type_name).ok_or_else(|| {
+            Diagnostic::at_span(
+                format!("Alter type references unknown type: {}", type_name),
+                alter_type.name.span(),
+            )
+        })?;
+        let enum_type = &mut engine_data.enums[index];
+        let position = |values: &[String], value: &Ident| {
+            values
+                .iter()
+                .position(|existing| *existing == value.value)
+                .ok_or_else(|| {
+                    Diagnostic::at_span(
+                        format!("type {} has no value {}", type_name, value),
+                        value.span,
+                    )
+                })
+        };
+
+        match alter_type.operation {
+            AlterTypeOperation::AddValue(add) => {
+                if enum_type.values.contains(&add.value.value) {
+                    if add.if_not_exists {
+                        return Ok(());
+                    }
+                    return Err(Diagnostic::at_span(
+                        format!("type {} already has a value {}", type_name, add.value),
+                        add.value.span,
+                    )
+                    .into());
+                }
+                let index = match &add.position {
+                    Some(AlterTypeAddValuePosition::Before(before)) => {
+                        position(&enum_type.values, before)?
+                    }
+                    Some(AlterTypeAddValuePosition::After(after)) => {
+                        position(&enum_type.values, after)? + 1
+                    }
+                    None => enum_type.values.len(),
+                };
+                enum_type.values.insert(index, add.value.value);
+            }
+            AlterTypeOperation::RenameValue(rename) => {
+                let index = position(&enum_type.values, &rename.from)?;
+                enum_type.values[index] = rename.to.value;
+            }
+            AlterTypeOperation::Rename(rename) => {
+                let new_name = rename.new_name.value;
+                enum_type.name = new_name.clone();
+                Self::rename_type_references(engine_data, &type_name, &new_name);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Applies `DROP TYPE`. PostgreSQL only drops a type columns still use with `CASCADE`,
+    /// which drops the columns along with it.
+    pub(crate) fn drop_type(
+        engine_data: &mut EngineData,
+        name: &ObjectName,
+        if_exists: bool,
+        cascade: bool,
+    ) -> Result<()> {
+        let type_name = object_name_last(name);
+        let Some(index) = engine_data.enum_index(&type_name) else {
+            if if_exists {
+                return Ok(());
+            }
+            return Err(Diagnostic::at_span(
+                format!("Drop type references unknown type: {}", name),
+                name.span(),
+            )
+            .into());
+        };
+
+        let users = engine_data
+            .tables
+            .iter()
+            .flat_map(|table| {
+                table
+                    .columns
+                    .iter()
+                    .filter(|column| is_of_type(column, &type_name))
+                    .map(move |column| format!("{}.{}", table.name, column.name))
+            })
+            .collect::<Vec<_>>();
+        if !users.is_empty() && !cascade {
+            return Err(Diagnostic::at_span(
+                format!(
+                    "cannot drop type {}, {} still use it, drop them first or add CASCADE",
+                    type_name,
+                    users.join(", ")
+                ),
+                name.span(),
+            )
+            .into());
+        }
+
+        for table in &mut engine_data.tables {
+            let dropped = table
+                .columns
+                .iter()
+                .filter(|column| is_of_type(column, &type_name))
+                .map(|column| column.name.clone())
+                .collect::<Vec<_>>();
+            for column in dropped {
+                table.columns.retain(|existing| existing.name != column);
+                table.drop_column_references(&column);
+            }
+        }
+        engine_data.enums.remove(index);
+        Ok(())
+    }
+
+    /// Points the columns of a renamed enum type at its new name
+    fn rename_type_references(engine_data: &mut EngineData, old: &str, new: &str) {
+        let columns = engine_data
+            .tables
+            .iter_mut()
+            .flat_map(|table| &mut table.columns);
+        for column in columns {
+            let DataType::Custom(_, modifiers) = &column.data_type.sql_type else {
+                continue;
+            };
+            if !is_of_type(column, old) {
+                continue;
+            }
+
+            column.data_type.sql_type =
+                DataType::Custom(ObjectName::from(vec![Ident::new(new)]), modifiers.clone());
+            column.data_type.php_type = PhpDataType::new(
+                PhpType::Enum(php_class_name(new)),
+                column.data_type.nullable,
+            );
+        }
+    }
+}
+
+/// Whether `column` is of the enum type called `name`
+fn is_of_type(column: &Column, name: &str) -> bool {
+    matches!(
+        &column.data_type.sql_type,
+        DataType::Custom(type_name, _) if object_name_last(type_name).eq_ignore_ascii_case(name)
+    )
+}
@@ -1,7 +1,17 @@
+mod constraints;
+mod enums;
+mod params;
 mod php_types;
+mod query;
+mod resolver;
 mod schema;
+mod scope;
 mod types;
 
+pub use params::*;
 pub use php_types::*;
+pub use query::*;
+pub use resolver::*;
 pub use schema::*;
+pub use scope::*;
 pub use types::*;
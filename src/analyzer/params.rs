@@ -0,0 +1,743 @@
+use crate::analyzer::scope::{Scope, object_name_last};
+use crate::analyzer::types::{Column, EngineData, Generated, Table};
+use crate::analyzer::{PhpDataType, PhpType, QueryParam, map_sql_to_php_data_type};
+use crate::configuration::{DecimalStrategy, Engine, TypeSettings, UnsignedBigIntStrategy};
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::parser::{LineColumn, placeholder_number};
+use anyhow::Result;
+use sqlparser::ast::*;
+use sqlparser::tokenizer::Span;
+
+/// Infers a PHP type for every placeholder of a statement from the column it is
+/// compared with or assigned to. While walking the statement it also reports column
+/// references the schema does not know and literals that cannot fit their column.
+pub struct ParamInference<'a> {
+    engine_data: &'a EngineData,
+    hints: Vec<PlaceholderHint>,
+    /// Projection aliases, which ORDER BY, GROUP BY and HAVING may refer to
+    aliases: Vec<String>,
+    diagnostics: Diagnostics,
+}
+
+struct PlaceholderHint {
+    location: LineColumn,
+    hint: Option<TypeHint>,
+}
+
+#[derive(Clone)]
+struct TypeHint {
+    /// Column the value is compared with or assigned to, which names the parameter
+    column: Option<String>,
+    php_type: PhpDataType,
+    /// PHP default of a value inserted into a column with a constant default
+    default_value: Option<String>,
+    /// SQL the database fills the column in with when the value is null
+    database_default: Option<String>,
+}
+
+impl TypeHint {
+    /// A value compared with a column is never null, so the parameter is not nullable
+    fn compared_with(column: &Column) -> Self {
+        Self {
+            column: Some(column.name.clone()),
+            php_type: PhpDataType::new(column.data_type.php_type.php_type.clone(), false),
+            default_value: None,
+            database_default: None,
+        }
+    }
+
+    /// A value assigned to a column takes over its nullability
+    fn assigned_to(column: &Column) -> Self {
+        Self {
+            column: Some(column.name.clone()),
+            php_type: column.data_type.php_type.clone(),
+            default_value: None,
+            database_default: None,
+        }
+    }
+
+    /// A value inserted into a column can be left out when the column has a default or is
+    /// generated. A constant default becomes the default of the parameter. Otherwise a NOT NULL
+    /// column takes null for the database to fill the column in, a nullable one has to keep
+    /// null for NULL.
+    fn inserted_into(column: &Column, table: &Table, engine: Engine) -> Self {
+        let assigned = Self::assigned_to(column);
+        if let Some(default_value) = column
+            .default
+            .as_ref()
+            .and_then(|default| default.php_value.clone())
+        {
+            return Self {
+                default_value: Some(default_value),
+                ..assigned
+            };
+        }
+        if column.data_type.nullable {
+            return assigned;
+        }
+
+        let database_default = match (&column.default, &column.generated) {
+            (Some(default), _) => Some(default.expr.to_string()),
+            // MySQL and SQLite number a NULL themselves, PostgreSQL needs the sequence
+            (None, Some(Generated::AutoIncrement | Generated::Identity)) => {
+                (engine == Engine::PostgreSql).then(|| {
+                    format!(
+                        "nextval(pg_get_serial_sequence('{}', '{}'))",
+                        table.name, column.name
+                    )
+                })
+            }
+            _ => return assigned,
+        };
+        Self {
+            php_type: PhpDataType::new(assigned.php_type.php_type.clone(), true),
+            default_value: Some("null".to_string()),
+            database_default,
+            ..assigned
+        }
+    }
+
+    fn of(php_type: PhpType, column: Option<String>) -> Self {
+        Self {
+            column,
+            php_type: PhpDataType::new(php_type, false),
+            default_value: None,
+            database_default: None,
+        }
+    }
+}
+
+impl<'a> ParamInference<'a> {
+    /// Builds the method parameters of a query and the parameter bound to each placeholder,
+    /// matching the hints of the statement to placeholders by their `locations`. Named (`:id`)
+    /// and numbered (`$1`) placeholders that repeat share a parameter.
+    pub fn infer(
+        engine_data: &'a EngineData,
+        statement: &Statement,
+        placeholders: &[String],
+        locations: &[LineColumn],
+    ) -> Result<(Vec<QueryParam>, Vec<String>)> {
+        let mut inference = Self {
+            engine_data,
+            hints: vec![],
+            aliases: vec![],
+            diagnostics: Diagnostics::default(),
+        };
+        inference.visit_statement(statement, None)?;
+        std::mem::take(&mut inference.diagnostics).into_result()?;
+
+        let mut params: Vec<QueryParam> = vec![];
+        let mut keys: Vec<String> = vec![];
+        let mut bindings = vec![];
+
+        for (placeholder, location) in placeholders.iter().zip(locations) {
+            // Placeholders inside expressions the walk does not look into get no hint
+            let hint = inference
+                .hints
+                .iter()
+                .find(|hint| hint.location == *location)
+                .and_then(|hint| hint.hint.clone());
+
+            let existing = match placeholder.as_str() {
+                "?" => None,
+                key => keys.iter().position(|existing| existing == key),
+            };
+
+            let index = match existing {
+                Some(index) => {
+                    if let Some(hint) =
+                        hint.filter(|_| params[index].php_type.php_type == PhpType::Mixed)
+                    {
+                        params[index].php_type = hint.php_type;
+                    }
+                    index
+                }
+                None => {
+                    let name = match placeholder.strip_prefix(':') {
+                        Some(name) => name.to_string(),
+                        None => {
+                            let base = hint
+                                .as_ref()
+                                .and_then(|hint| hint.column.clone())
+                                .unwrap_or_else(|| format!("arg{}", params.len() + 1));
+                            Self::unique_name(&params, base)
+                        }
+                    };
+                    keys.push(placeholder.clone());
+                    let (php_type, default_value, database_default) = match hint {
+                        Some(hint) => (hint.php_type, hint.default_value, hint.database_default),
+                        None => (PhpDataType::new(PhpType::Mixed, false), None, None),
+                    };
+                    params.push(QueryParam {
+                        name,
+                        php_type,
+                        default_value,
+                        database_default,
+                    });
+                    params.len() - 1
+                }
+            };
+
+            bindings.push(params[index].name.clone());
+        }
+
+        // Numbered placeholders may be used in any order, the method takes them by number
+        if keys.iter().all(|key| placeholder_number(key).is_some()) {
+            let mut numbered: Vec<_> = keys.iter().zip(params).collect();
+            numbered.sort_by_key(|(key, _)| placeholder_number(key));
+            params = numbered.into_iter().map(|(_, param)| param).collect();
+        }
+
+        // PHP only lets parameters after the last required one be optional
+        let required = params
+            .iter()
+            .rposition(|param| param.default_value.is_none())
+            .map_or(0, |index| index + 1);
+        for param in &mut params[..required] {
+            param.default_value = None;
+        }
+
+        Ok((params, bindings))
+    }
+
+    fn unique_name(params: &[QueryParam], base: String) -> String {
+        let taken = |name: &str| params.iter().any(|param| param.name == name);
+        if !taken(&base) {
+            return base;
+        }
+        (2..)
+            .map(|suffix| format!("{}_{}", base, suffix))
+            .find(|name| !taken(name))
+            .unwrap_or(base)
+    }
+
+    fn visit_statement(&mut self, statement: &Statement, parent: Option<&Scope>) -> Result<()> {
+        match statement {
+            Statement::Query(query) => self.visit_query(query, parent),
+            Statement::Insert(insert) => {
+                let mut scope = Scope::new(parent);
+                let TableObject::TableName(name) = &insert.table else {
+                    return Ok(());
+                };
+                scope.add_table(self.engine_data, name, None)?;
+                let table = scope.relations[0].table;
+
+                let columns: Vec<Option<&Column>> = if insert.columns.is_empty() {
+                    table.columns.iter().map(Some).collect()
+                } else {
+                    insert
+                        .columns
+                        .iter()
+                        .map(|ident| {
+                            let column = table.find_column(&ident.value);
+                            if column.is_none() {
+                                self.diagnostics.push(Diagnostic::at_span(
+                                    format!("unknown column {} in table {}", ident, table.name),
+                                    ident.span,
+                                ));
+                            }
+                            column
+                        })
+                        .collect()
+                };
+
+                if let Some(source) = &insert.source {
+                    match &*source.body {
+                        SetExpr::Values(values) => {
+                            for row in &values.rows {
+                                if row.len() != columns.len() {
+                                    self.diagnostics.push(Diagnostic::at_span(
+                                        format!(
+                                            "INSERT has {} columns but {} values",
+                                            columns.len(),
+                                            row.len()
+                                        ),
+                                        row.first()
+                                            .map(|expr| expr.span())
+                                            .unwrap_or(Span::empty()),
+                                    ));
+                                }
+                                for (index, expr) in row.iter().enumerate() {
+                                    let column = columns.get(index).copied().flatten();
+                                    self.check_literal(column, expr);
+                                    // Only a bare placeholder can stand in for the default
+                                    let hint = column.map(|column| match expr {
+                                        Expr::Value(ValueWithSpan {
+                                            value: Value::Placeholder(_),
+                                            ..
+                                        }) => TypeHint::inserted_into(
+                                            column,
+                                            table,
+                                            self.engine_data.engine,
+                                        ),
+                                        _ => TypeHint::assigned_to(column),
+                                    });
+                                    self.visit_expr(expr, &scope, hint)?;
+                                }
+                            }
+                        }
+                        _ => self.visit_query(source, Some(&scope))?,
+                    }
+                }
+
+                if let Some(OnInsert::DuplicateKeyUpdate(assignments)) = &insert.on {
+                    self.visit_assignments(assignments, &scope)?;
+                }
+                if let Some(returning) = &insert.returning {
+                    self.visit_select_items(returning, &scope)?;
+                }
+                Ok(())
+            }
+            Statement::Update {
+                table,
+                assignments,
+                from,
+                selection,
+                returning,
+                ..
+            } => {
+                let mut scope = Scope::new(parent);
+                scope.add_table_with_joins(self.engine_data, table)?;
+                if let Some(
+                    UpdateTableFromKind::BeforeSet(from) | UpdateTableFromKind::AfterSet(from),
+                ) = from
+                {
+                    for table in from {
+                        scope.add_table_with_joins(self.engine_data, table)?;
+                    }
+                }
+
+                self.visit_assignments(assignments, &scope)?;
+                if let Some(selection) = selection {
+                    self.visit_expr(selection, &scope, None)?;
+                }
+                if let Some(returning) = returning {
+                    self.visit_select_items(returning, &scope)?;
+                }
+                Ok(())
+            }
+            Statement::Delete(delete) => {
+                let mut scope = Scope::new(parent);
+                let (FromTable::WithFromKeyword(from) | FromTable::WithoutKeyword(from)) =
+                    &delete.from;
+                for table in from.iter().chain(delete.using.iter().flatten()) {
+                    scope.add_table_with_joins(self.engine_data, table)?;
+                }
+
+                if let Some(selection) = &delete.selection {
+                    self.visit_expr(selection, &scope, None)?;
+                }
+                for order_by in &delete.order_by {
+                    self.visit_expr(&order_by.expr, &scope, None)?;
+                }
+                if let Some(limit) = &delete.limit {
+                    let hint = TypeHint::of(PhpType::Int, Some("limit".to_string()));
+                    self.visit_expr(limit, &scope, Some(hint))?;
+                }
+                if let Some(returning) = &delete.returning {
+                    self.visit_select_items(returning, &scope)?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn visit_query(&mut self, query: &Query, parent: Option<&Scope>) -> Result<()> {
+        if let Some(with) = &query.with {
+            // `QueryAnalyzer` only adds the tables of the outermost WITH to the schema
+            if parent.is_some() {
+                return Err(Diagnostic::at_span(
+                    "WITH is only supported at the start of a query, not in a subquery",
+                    with.with_token.0.span,
+                )
+                .into());
+            }
+            for cte in &with.cte_tables {
+                self.visit_query(&cte.query, parent)?;
+            }
+        }
+
+        let scope = self.visit_set_expr(&query.body, parent)?;
+        let scope = scope.unwrap_or_else(|| Scope::new(parent));
+
+        if let Some(OrderBy {
+            kind: OrderByKind::Expressions(exprs),
+            ..
+        }) = &query.order_by
+        {
+            for order_by in exprs {
+                self.visit_expr(&order_by.expr, &scope, None)?;
+            }
+        }
+        if let Some(limit) = &query.limit {
+            let hint = TypeHint::of(PhpType::Int, Some("limit".to_string()));
+            self.visit_expr(limit, &scope, Some(hint))?;
+        }
+        if let Some(offset) = &query.offset {
+            let hint = TypeHint::of(PhpType::Int, Some("offset".to_string()));
+            self.visit_expr(&offset.value, &scope, Some(hint))?;
+        }
+        Ok(())
+    }
+
+    /// Visits a query body and returns the scope of its outermost SELECT, so that
+    /// ORDER BY and LIMIT can resolve columns against it
+    fn visit_set_expr<'s>(
+        &mut self,
+        body: &SetExpr,
+        parent: Option<&'s Scope<'s>>,
+    ) -> Result<Option<Scope<'s>>>
+    where
+        'a: 's,
+    {
+        match body {
+            SetExpr::Select(select) => {
+                let mut scope = Scope::new(parent);
+                for from in &select.from {
+                    scope.add_table_with_joins(self.engine_data, from)?;
+                }
+
+                for item in &select.projection {
+                    if let SelectItem::ExprWithAlias { alias, .. } = item {
+                        self.aliases.push(alias.value.clone());
+                    }
+                }
+                self.visit_select_items(&select.projection, &scope)?;
+                for from in &select.from {
+                    for join in &from.joins {
+                        if let Some(JoinConstraint::On(on)) = join_constraint(&join.join_operator) {
+                            self.visit_expr(on, &scope, None)?;
+                        }
+                    }
+                }
+                if let Some(selection) = &select.selection {
+                    self.visit_expr(selection, &scope, None)?;
+                }
+                if let GroupByExpr::Expressions(exprs, _) = &select.group_by {
+                    for expr in exprs {
+                        self.visit_expr(expr, &scope, None)?;
+                    }
+                }
+                if let Some(having) = &select.having {
+                    self.visit_expr(having, &scope, None)?;
+                }
+                Ok(Some(scope))
+            }
+            SetExpr::Query(query) => {
+                self.visit_query(query, parent)?;
+                Ok(None)
+            }
+            SetExpr::SetOperation { left, right, .. } => {
+                let scope = self.visit_set_expr(left, parent)?;
+                self.visit_set_expr(right, parent)?;
+                Ok(scope)
+            }
+            SetExpr::Values(values) => {
+                let scope = Scope::new(parent);
+                for row in &values.rows {
+                    for expr in row {
+                        self.visit_expr(expr, &scope, None)?;
+                    }
+                }
+                Ok(None)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn visit_select_items(&mut self, items: &[SelectItem], scope: &Scope) -> Result<()> {
+        for item in items {
+            match item {
+                SelectItem::UnnamedExpr(expr) | SelectItem::ExprWithAlias { expr, .. } => {
+                    self.visit_expr(expr, scope, None)?
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_assignments(&mut self, assignments: &[Assignment], scope: &Scope) -> Result<()> {
+        for assignment in assignments {
+            let column = match &assignment.target {
+                AssignmentTarget::ColumnName(name) => {
+                    let column =
+                        scope.resolve_column(&Expr::Identifier(Ident::new(object_name_last(name))));
+                    if column.is_none() {
+                        self.diagnostics.push(Diagnostic::at_span(
+                            format!("unknown column {}", name),
+                            name.span(),
+                        ));
+                    }
+                    column
+                }
+                AssignmentTarget::Tuple(_) => None,
+            };
+            self.check_literal(column, &assignment.value);
+            self.visit_expr(&assignment.value, scope, column.map(TypeHint::assigned_to))?;
+        }
+        Ok(())
+    }
+
+    fn visit_expr(&mut self, expr: &Expr, scope: &Scope, hint: Option<TypeHint>) -> Result<()> {
+        let compared = |other: &Expr| scope.resolve_column(other).map(TypeHint::compared_with);
+        let pattern = |other: &Expr| {
+            let column = scope
+                .resolve_column(other)
+                .map(|column| column.name.clone());
+            Some(TypeHint::of(PhpType::String, column))
+        };
+
+        match expr {
+            Expr::Identifier(_) | Expr::CompoundIdentifier(_) => self.check_column(expr, scope),
+            Expr::Value(ValueWithSpan {
+                value: Value::Placeholder(_),
+                span,
+            }) => {
+                self.hints.push(PlaceholderHint {
+                    location: (span.start.line, span.start.column),
+                    hint,
+                });
+            }
+            Expr::BinaryOp { left, op, right } => match op {
+                BinaryOperator::Eq
+                | BinaryOperator::NotEq
+                | BinaryOperator::Lt
+                | BinaryOperator::LtEq
+                | BinaryOperator::Gt
+                | BinaryOperator::GtEq
+                | BinaryOperator::Spaceship => {
+                    self.check_literal(scope.resolve_column(left), right);
+                    self.check_literal(scope.resolve_column(right), left);
+                    self.visit_expr(left, scope, compared(right))?;
+                    self.visit_expr(right, scope, compared(left))?;
+                }
+                BinaryOperator::Plus
+                | BinaryOperator::Minus
+                | BinaryOperator::Multiply
+                | BinaryOperator::Divide
+                | BinaryOperator::Modulo => {
+                    self.visit_expr(left, scope, hint.clone())?;
+                    self.visit_expr(right, scope, hint)?;
+                }
+                _ => {
+                    self.visit_expr(left, scope, None)?;
+                    self.visit_expr(right, scope, None)?;
+                }
+            },
+            Expr::IsDistinctFrom(left, right) | Expr::IsNotDistinctFrom(left, right) => {
+                self.visit_expr(left, scope, compared(right))?;
+                self.visit_expr(right, scope, compared(left))?;
+            }
+            Expr::AnyOp { left, right, .. } | Expr::AllOp { left, right, .. } => {
+                self.visit_expr(left, scope, None)?;
+                self.visit_expr(right, scope, compared(left))?;
+            }
+            Expr::InList { expr, list, .. } => {
+                self.visit_expr(expr, scope, None)?;
+                for item in list {
+                    self.visit_expr(item, scope, compared(expr))?;
+                }
+            }
+            Expr::Between {
+                expr, low, high, ..
+            } => {
+                self.visit_expr(expr, scope, None)?;
+                self.visit_expr(low, scope, compared(expr))?;
+                self.visit_expr(high, scope, compared(expr))?;
+            }
+            Expr::Like {
+                expr, pattern: p, ..
+            }
+            | Expr::ILike {
+                expr, pattern: p, ..
+            }
+            | Expr::SimilarTo {
+                expr, pattern: p, ..
+            }
+            | Expr::RLike {
+                expr, pattern: p, ..
+            } => {
+                self.visit_expr(expr, scope, None)?;
+                self.visit_expr(p, scope, pattern(expr))?;
+            }
+            Expr::Cast {
+                expr, data_type, ..
+            } => {
+                let hint = TypeHint {
+                    column: hint.and_then(|hint| hint.column),
+                    php_type: map_sql_to_php_data_type(
+                        data_type,
+                        false,
+                        self.engine_data.engine,
+                        self.engine_data.types,
+                    ),
+                    default_value: None,
+                    database_default: None,
+                };
+                self.visit_expr(expr, scope, Some(hint))?;
+            }
+            Expr::Nested(inner) | Expr::UnaryOp { expr: inner, .. } => {
+                self.visit_expr(inner, scope, hint)?;
+            }
+            Expr::IsNull(inner)
+            | Expr::IsNotNull(inner)
+            | Expr::IsTrue(inner)
+            | Expr::IsNotTrue(inner)
+            | Expr::IsFalse(inner)
+            | Expr::IsNotFalse(inner) => {
+                self.visit_expr(inner, scope, None)?;
+            }
+            Expr::InSubquery { expr, subquery, .. } => {
+                self.visit_expr(expr, scope, None)?;
+                self.visit_query(subquery, Some(scope))?;
+            }
+            Expr::Exists { subquery, .. } | Expr::Subquery(subquery) => {
+                self.visit_query(subquery, Some(scope))?;
+            }
+            Expr::Tuple(items) => {
+                for item in items {
+                    self.visit_expr(item, scope, None)?;
+                }
+            }
+            Expr::Case {
+                operand,
+                conditions,
+                else_result,
+            } => {
+                if let Some(operand) = operand {
+                    self.visit_expr(operand, scope, None)?;
+                }
+                for when in conditions {
+                    let condition_hint = operand.as_deref().and_then(compared);
+                    self.visit_expr(&when.condition, scope, condition_hint)?;
+                    self.visit_expr(&when.result, scope, hint.clone())?;
+                }
+                if let Some(else_result) = else_result {
+                    self.visit_expr(else_result, scope, hint)?;
+                }
+            }
+            Expr::Function(function) => {
+                if let FunctionArguments::List(list) = &function.args {
+                    for arg in &list.args {
+                        match arg {
+                            FunctionArg::Unnamed(FunctionArgExpr::Expr(expr))
+                            | FunctionArg::Named {
+                                arg: FunctionArgExpr::Expr(expr),
+                                ..
+                            } => self.visit_expr(expr, scope, None)?,
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+impl ParamInference<'_> {
+    fn check_column(&mut self, expr: &Expr, scope: &Scope) {
+        if scope.lookup(expr).is_some() || scope.is_opaque(expr) {
+            return;
+        }
+        if let Expr::Identifier(ident) = expr {
+            let is_alias = self
+                .aliases
+                .iter()
+                .any(|alias| alias.eq_ignore_ascii_case(&ident.value));
+            // DEFAULT in VALUES and MySQL @variables are not columns
+            if is_alias
+                || ident.value.eq_ignore_ascii_case("DEFAULT")
+                || ident.value.starts_with('@')
+            {
+                return;
+            }
+        }
+
+        self.diagnostics.push(Diagnostic::at_span(
+            format!("unknown column {}", expr),
+            expr.span(),
+        ));
+    }
+
+    /// Flags string literals that can never be a value of a numeric or boolean column
+    fn check_literal(&mut self, column: Option<&Column>, expr: &Expr) {
+        let (
+            Some(column),
+            Expr::Value(ValueWithSpan {
+                value: Value::SingleQuotedString(text) | Value::DoubleQuotedString(text),
+                span,
+            }),
+        ) = (column, expr)
+        else {
+            return;
+        };
+
+        // Decimals and unsigned big integers are numbers whichever PHP type they map to
+        let as_numbers = TypeSettings {
+            decimal: DecimalStrategy::Float,
+            unsigned_bigint: UnsignedBigIntStrategy::Int,
+            ..Default::default()
+        };
+        let sql_type = &column.data_type.sql_type;
+        let numeric = matches!(
+            map_sql_to_php_data_type(sql_type, false, self.engine_data.engine, as_numbers).php_type,
+            PhpType::Int | PhpType::Float | PhpType::Bool
+        );
+        if numeric && text.trim().parse::<f64>().is_err() {
+            self.diagnostics.push(Diagnostic::at_span(
+                format!(
+                    "type mismatch: column {} is {} but '{}' is not a number",
+                    column.name, column.data_type.php_type.simple_type, text
+                ),
+                *span,
+            ));
+        }
+
+        // A SET takes a comma separated list of its values
+        let given: Vec<&str> = if column.data_type.php_type.is_enum_set {
+            text.split(',').filter(|value| !value.is_empty()).collect()
+        } else {
+            vec![text]
+        };
+        if let Some(values) = column.data_type.enum_values(&self.engine_data.enums)
+            && let Some(text) = given
+                .iter()
+                .find(|value| !values.iter().any(|v| v == *value))
+        {
+            self.diagnostics.push(Diagnostic::at_span(
+                format!(
+                    "type mismatch: '{}' is not a value of column {}, expected one of {}",
+                    text,
+                    column.name,
+                    values.join(", ")
+                ),
+                *span,
+            ));
+        }
+    }
+}
+
+pub(crate) fn join_constraint(operator: &JoinOperator) -> Option<&JoinConstraint> {
+    match operator {
+        JoinOperator::Join(constraint)
+        | JoinOperator::Inner(constraint)
+        | JoinOperator::Left(constraint)
+        | JoinOperator::LeftOuter(constraint)
+        | JoinOperator::Right(constraint)
+        | JoinOperator::RightOuter(constraint)
+        | JoinOperator::FullOuter(constraint)
+        | JoinOperator::Semi(constraint)
+        | JoinOperator::LeftSemi(constraint)
+        | JoinOperator::RightSemi(constraint)
+        | JoinOperator::Anti(constraint)
+        | JoinOperator::LeftAnti(constraint)
+        | JoinOperator::RightAnti(constraint)
+        | JoinOperator::AsOf { constraint, .. } => Some(constraint),
+        JoinOperator::CrossJoin | JoinOperator::CrossApply | JoinOperator::OuterApply => None,
+    }
+}
@@ -1,5 +1,9 @@
+use crate::analyzer::object_name_last;
+use crate::configuration::{
+    DateTimeStrategy, DecimalStrategy, Engine, TypeSettings, UnsignedBigIntStrategy,
+};
 use serde::Serialize;
-use sqlparser::ast::DataType;
+use sqlparser::ast::{DataType, Expr, TimezoneInfo, UnaryOperator, Value, ValueWithSpan};
 use std::fmt;
 
 #[derive(Debug, Clone, PartialEq, Serialize)]
@@ -9,11 +13,68 @@ pub enum PhpType {
     String,
     Bool,
     Array,
+    /// A JSON document, decoded into an array
+    Json,
     Mixed,
-    DateTime,
+    DateTime(DateTimeType),
+    /// `Brick\Math\BigDecimal` from `brick/math`, by class name
+    BigDecimal(String),
+    /// A backed enum, by PHP class name
+    Enum(String),
+    /// A MySQL `SET`, a list of cases of the enum
+    EnumSet(String),
+    /// A MySQL `SET` as a list of its values, where PHP has no enums
+    Set,
     Nullable(Box<PhpType>),
 }
 
+impl PhpType {
+    /// The same type with every class name it refers to passed through `name`
+    pub fn map_classes(&self, name: &mut impl FnMut(&str) -> String) -> PhpType {
+        match self {
+            PhpType::DateTime(date_time) => PhpType::DateTime(DateTimeType {
+                class: name(&date_time.class),
+                ..date_time.clone()
+            }),
+            PhpType::BigDecimal(class) => PhpType::BigDecimal(name(class)),
+            PhpType::Enum(class) => PhpType::Enum(name(class)),
+            PhpType::EnumSet(class) => PhpType::EnumSet(name(class)),
+            PhpType::Nullable(inner) => PhpType::Nullable(Box::new(inner.map_classes(name))),
+            php_type => php_type.clone(),
+        }
+    }
+
+    /// The same type for PHP versions without enums, which take their values as strings
+    pub fn without_enums(&self) -> PhpType {
+        match self {
+            PhpType::Enum(_) => PhpType::String,
+            PhpType::EnumSet(_) => PhpType::Set,
+            PhpType::Nullable(inner) => PhpType::Nullable(Box::new(inner.without_enums())),
+            php_type => php_type.clone(),
+        }
+    }
+}
+
+/// A date/time class such as `\DateTimeImmutable` and how values of one SQL type are written
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DateTimeType {
+    pub class: String,
+    /// Format values are bound in, see `DateTimeInterface::format()`
+    pub format: String,
+    /// Whether the database returns values in exactly `format`, otherwise they are left to
+    /// the constructor, which also takes optional fractions and offsets
+    pub exact: bool,
+}
+
+/// Kinds of SQL date/time types that map to a date/time class
+#[derive(Clone, Copy)]
+enum DateKind {
+    Date,
+    /// A date and time without time zone, with the precision of its fractional seconds
+    DateTime(Option<u64>),
+    DateTimeTz,
+}
+
 impl fmt::Display for PhpType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -21,21 +82,34 @@ impl fmt::Display for PhpType {
             PhpType::Float => write!(f, "float"),
             PhpType::String => write!(f, "string"),
             PhpType::Bool => write!(f, "bool"),
-            PhpType::Array => write!(f, "array"),
+            PhpType::Array | PhpType::Json => write!(f, "array"),
             PhpType::Mixed => write!(f, "mixed"),
-            PhpType::DateTime => write!(f, "DateTime"),
+            PhpType::DateTime(date_time) => write!(f, "{}", date_time.class),
+            PhpType::BigDecimal(class) => write!(f, "{}", class),
+            PhpType::Enum(class) => write!(f, "{}", class),
+            PhpType::EnumSet(_) | PhpType::Set => write!(f, "array"),
             PhpType::Nullable(inner) => write!(f, "?{}", inner),
         }
     }
 }
 
-#[derive(Serialize)]
+#[derive(Clone, Serialize)]
 pub struct PhpDataType {
     pub php_type: PhpType,
     pub is_nullable: bool,
     pub docblock_type: String,
     pub type_hint: String,
     pub simple_type: String,
+    pub is_big_decimal: bool,
+    pub is_json: bool,
+    /// Format of date/time values, see `DateTimeType`
+    pub date_format: Option<String>,
+    pub date_format_exact: bool,
+    pub is_enum: bool,
+    pub is_enum_set: bool,
+    pub is_set: bool,
+    /// Class of the enum, for enums and sets of enum cases
+    pub enum_class: Option<String>,
 }
 
 impl PhpDataType {
@@ -49,10 +123,18 @@ impl PhpDataType {
             (PhpType::String, true) => "?string".to_string(),
             (PhpType::Bool, false) => "bool".to_string(),
             (PhpType::Bool, true) => "?bool".to_string(),
-            (PhpType::Array, false) => "array".to_string(),
-            (PhpType::Array, true) => "?array".to_string(),
-            (PhpType::DateTime, false) => "DateTime".to_string(),
-            (PhpType::DateTime, true) => "?DateTime".to_string(),
+            (PhpType::Array | PhpType::Json, false) => "array".to_string(),
+            (PhpType::Array | PhpType::Json, true) => "?array".to_string(),
+            (PhpType::DateTime(date_time), false) => date_time.class.clone(),
+            (PhpType::DateTime(date_time), true) => format!("?{}", date_time.class),
+            (PhpType::BigDecimal(class), false) => class.clone(),
+            (PhpType::BigDecimal(class), true) => format!("?{}", class),
+            (PhpType::Enum(class), false) => class.clone(),
+            (PhpType::Enum(class), true) => format!("?{}", class),
+            (PhpType::EnumSet(class), false) => format!("list<{}>", class),
+            (PhpType::EnumSet(class), true) => format!("list<{}>|null", class),
+            (PhpType::Set, false) => "list<string>".to_string(),
+            (PhpType::Set, true) => "list<string>|null".to_string(),
             (PhpType::Mixed, _) => "mixed".to_string(),
             (PhpType::Nullable(inner), _) => format!("?{}", inner),
         };
@@ -62,19 +144,40 @@ impl PhpDataType {
             PhpType::Float => "float".to_string(),
             PhpType::String => "string".to_string(),
             PhpType::Bool => "bool".to_string(),
-            PhpType::Array => "array".to_string(),
-            PhpType::DateTime => "DateTime".to_string(),
+            PhpType::Array | PhpType::Json => "array".to_string(),
+            PhpType::DateTime(date_time) => date_time.class.clone(),
+            PhpType::BigDecimal(class) => class.clone(),
+            PhpType::Enum(class) => class.clone(),
+            PhpType::EnumSet(_) | PhpType::Set => "array".to_string(),
             PhpType::Mixed => "mixed".to_string(),
             PhpType::Nullable(inner) => (**inner).to_string(),
         };
 
-        let type_hint = if is_nullable {
+        let type_hint = if is_nullable && php_type != PhpType::Mixed {
             format!("?{}", &simple_type)
         } else {
             simple_type.clone()
         };
 
+        let (date_format, date_format_exact) = match &php_type {
+            PhpType::DateTime(date_time) => (Some(date_time.format.clone()), date_time.exact),
+            _ => (None, false),
+        };
+
+        let enum_class = match &php_type {
+            PhpType::Enum(class) | PhpType::EnumSet(class) => Some(class.clone()),
+            _ => None,
+        };
+
         Self {
+            is_big_decimal: matches!(php_type, PhpType::BigDecimal(_)),
+            is_json: php_type == PhpType::Json,
+            date_format,
+            date_format_exact,
+            is_enum: matches!(php_type, PhpType::Enum(_)),
+            is_enum_set: matches!(php_type, PhpType::EnumSet(_)),
+            is_set: php_type == PhpType::Set,
+            enum_class,
             php_type,
             is_nullable,
             docblock_type,
@@ -82,20 +185,51 @@ impl PhpDataType {
             simple_type,
         }
     }
+
+    /// The same type with every class name it refers to passed through `name`
+    pub fn map_classes(&self, name: &mut impl FnMut(&str) -> String) -> PhpDataType {
+        PhpDataType::new(self.php_type.map_classes(name), self.is_nullable)
+    }
+
+    /// The same type for PHP versions without enums, see `PhpType::without_enums`
+    pub fn without_enums(&self) -> PhpDataType {
+        PhpDataType::new(self.php_type.without_enums(), self.is_nullable)
+    }
 }
 
-pub fn map_sql_to_php_data_type(sql_type: &DataType, is_nullable: bool) -> PhpDataType {
+/// Maps a column type to PHP. Types that only exist in some engines, or mean something
+/// different between them, are resolved for the configured `engine`. Decimals and unsigned
+/// 64-bit integers follow the strategies in `types`.
+pub fn map_sql_to_php_data_type(
+    sql_type: &DataType,
+    is_nullable: bool,
+    engine: Engine,
+    types: TypeSettings,
+) -> PhpDataType {
     let php_type = match sql_type {
-        DataType::Custom(custom, _) => {
-            let name = custom.0.first().unwrap().to_string();
-            if name == "SERIAL" {
-                PhpType::Int
-            } else {
-                PhpType::Mixed
-            }
-        }
+        _ if engine == Engine::Sqlite => map_sqlite_type(sql_type, types),
+
+        DataType::Custom(name, _) => map_custom_type(&object_name_last(name), engine, types),
 
-        DataType::Int(_) | DataType::SmallInt(_) | DataType::BigInt(_) => PhpType::Int,
+        // MySQL has no real boolean, BOOLEAN is an alias of TINYINT(1)
+        DataType::TinyInt(Some(1)) if engine == Engine::MySql => PhpType::Bool,
+
+        DataType::TinyInt(_)
+        | DataType::SmallInt(_)
+        | DataType::MediumInt(_)
+        | DataType::Int(_)
+        | DataType::Integer(_)
+        | DataType::BigInt(_) => PhpType::Int,
+
+        DataType::TinyIntUnsigned(_)
+        | DataType::SmallIntUnsigned(_)
+        | DataType::MediumIntUnsigned(_)
+        | DataType::IntUnsigned(_)
+        | DataType::IntegerUnsigned(_) => PhpType::Int,
+
+        DataType::BigIntUnsigned(_) | DataType::Int8Unsigned(_) | DataType::UInt64 => {
+            unsigned_bigint_type(types)
+        }
 
         DataType::Int2(_)
         | DataType::Int4(_)
@@ -104,7 +238,7 @@ pub fn map_sql_to_php_data_type(sql_type: &DataType, is_nullable: bool) -> PhpDa
         | DataType::Int32
         | DataType::Int64 => PhpType::Int,
 
-        DataType::UInt8 | DataType::UInt16 | DataType::UInt32 | DataType::UInt64 => PhpType::Int,
+        DataType::UInt8 | DataType::UInt16 | DataType::UInt32 => PhpType::Int,
 
         DataType::Float(_)
         | DataType::Float4
@@ -112,11 +246,20 @@ pub fn map_sql_to_php_data_type(sql_type: &DataType, is_nullable: bool) -> PhpDa
         | DataType::Float32
         | DataType::Float64 => PhpType::Float,
 
-        DataType::Decimal(_) | DataType::Real | DataType::DoublePrecision => PhpType::Float,
+        DataType::Decimal(_)
+        | DataType::Numeric(_)
+        | DataType::Dec(_)
+        | DataType::BigNumeric(_)
+        | DataType::BigDecimal(_) => decimal_type(types),
+
+        DataType::Real | DataType::Double(_) | DataType::DoublePrecision => PhpType::Float,
 
         DataType::Bool | DataType::Boolean => PhpType::Bool,
 
         DataType::Varchar(_)
+        | DataType::Nvarchar(_)
+        | DataType::CharacterVarying(_)
+        | DataType::CharVarying(_)
         | DataType::Varbinary(_)
         | DataType::VarBit(_)
         | DataType::Char(_)
@@ -124,17 +267,341 @@ pub fn map_sql_to_php_data_type(sql_type: &DataType, is_nullable: bool) -> PhpDa
         | DataType::CharLargeObject(_) => PhpType::String,
         DataType::String(_) | DataType::FixedString(_) => PhpType::String,
 
-        DataType::Array(_) | DataType::JSON | DataType::JSONB => PhpType::Array,
+        DataType::Text
+        | DataType::TinyText
+        | DataType::MediumText
+        | DataType::LongText
+        | DataType::Clob(_)
+        | DataType::Uuid => PhpType::String,
+
+        DataType::Binary(_)
+        | DataType::Blob(_)
+        | DataType::TinyBlob
+        | DataType::MediumBlob
+        | DataType::LongBlob
+        | DataType::Bytea => PhpType::String,
+
+        // PDO hands PostgreSQL intervals over in their text form
+        DataType::Interval => PhpType::String,
+
+        // PDO hands PostgreSQL arrays over in their `{a,b}` text form and cannot bind a PHP
+        // array, so they stay strings until there is a conversion for them
+        DataType::Array(_) => PhpType::String,
+        DataType::JSON | DataType::JSONB => PhpType::Json,
 
-        DataType::Date
-        | DataType::Date32
-        | DataType::Datetime(_)
-        | DataType::Datetime64(_, _)
-        | DataType::Time(_, _)
-        | DataType::Timestamp(_, _) => PhpType::DateTime,
+        DataType::Date | DataType::Date32 => date_time_type(DateKind::Date, engine, types),
+        DataType::Datetime(precision)
+        | DataType::Timestamp(precision, TimezoneInfo::None | TimezoneInfo::WithoutTimeZone) => {
+            date_time_type(DateKind::DateTime(*precision), engine, types)
+        }
+        DataType::Datetime64(precision, _) => {
+            date_time_type(DateKind::DateTime(Some(*precision)), engine, types)
+        }
+        DataType::Timestamp(_, TimezoneInfo::WithTimeZone | TimezoneInfo::Tz) => {
+            date_time_type(DateKind::DateTimeTz, engine, types)
+        }
+        // A time of day, or in MySQL a duration of up to 838 hours, has no date to go with
+        DataType::Time(_, _) => PhpType::String,
 
         _ => PhpType::Mixed,
     };
 
     PhpDataType::new(php_type, is_nullable)
 }
+
+/// Types sqlparser does not know itself, such as PostgreSQL's `SERIAL` or MySQL's `YEAR`
+fn map_custom_type(name: &str, engine: Engine, types: TypeSettings) -> PhpType {
+    match (name.to_uppercase().as_str(), engine) {
+        ("SERIAL", _) => PhpType::Int,
+        ("SMALLSERIAL" | "BIGSERIAL" | "SERIAL2" | "SERIAL4" | "SERIAL8", Engine::PostgreSql) => {
+            PhpType::Int
+        }
+        ("TIMESTAMPTZ", Engine::PostgreSql) => date_time_type(DateKind::DateTimeTz, engine, types),
+        ("TIMETZ", Engine::PostgreSql) => PhpType::String,
+        (
+            "MONEY" | "CITEXT" | "INET" | "CIDR" | "MACADDR" | "XML" | "TSVECTOR",
+            Engine::PostgreSql,
+        ) => PhpType::String,
+        ("YEAR", Engine::MySql) => PhpType::Int,
+        _ => PhpType::Mixed,
+    }
+}
+
+/// SQLite stores values by the affinity of the declared type rather than the type itself,
+/// see https://www.sqlite.org/datatype3.html#determination_of_column_affinity
+fn map_sqlite_type(sql_type: &DataType, types: TypeSettings) -> PhpType {
+    let name = sql_type.to_string().to_uppercase();
+    let contains = |parts: &[&str]| parts.iter().any(|part| name.contains(part));
+
+    if contains(&["INT"]) {
+        PhpType::Int
+    } else if contains(&["CHAR", "CLOB", "TEXT", "BLOB"]) {
+        PhpType::String
+    } else if name.is_empty() {
+        // Without a declared type a column keeps whatever it was given
+        PhpType::Mixed
+    } else if contains(&["REAL", "FLOA", "DOUB"]) {
+        PhpType::Float
+    } else if contains(&["BOOL"]) {
+        // NUMERIC affinity, from here on only types the hydrator can convert get a type
+        PhpType::Bool
+    } else if contains(&["DATE", "STAMP"]) {
+        let kind = if contains(&["TIME"]) {
+            DateKind::DateTime(None)
+        } else {
+            DateKind::Date
+        };
+        date_time_type(kind, Engine::Sqlite, types)
+    } else if contains(&["TIME"]) {
+        PhpType::String
+    } else if contains(&["JSON"]) {
+        PhpType::Json
+    } else if contains(&["NUMERIC", "DECIMAL"]) {
+        decimal_type(types)
+    } else {
+        PhpType::Mixed
+    }
+}
+
+/// SQLite keeps dates as whatever text was stored, so only MySQL and PostgreSQL values are
+/// read with an exact format. MySQL pads fractional seconds to the precision of the column
+/// where PostgreSQL trims them, which leaves MySQL columns without fractions exact.
+fn date_time_type(kind: DateKind, engine: Engine, types: TypeSettings) -> PhpType {
+    let (format, exact) = match (kind, engine) {
+        (DateKind::Date, Engine::Sqlite) => ("Y-m-d", false),
+        (DateKind::Date, _) => ("Y-m-d", true),
+        (DateKind::DateTime(None | Some(0)), Engine::MySql) => ("Y-m-d H:i:s", true),
+        (DateKind::DateTime(_), Engine::MySql | Engine::PostgreSql) => ("Y-m-d H:i:s.u", false),
+        (DateKind::DateTime(_), Engine::Sqlite) => ("Y-m-d H:i:s", false),
+        (DateKind::DateTimeTz, _) => ("Y-m-d H:i:s.uP", false),
+    };
+    let class = match types.date_time {
+        DateTimeStrategy::DateTimeImmutable => "\\DateTimeImmutable",
+        DateTimeStrategy::CarbonImmutable => "\\Carbon\\CarbonImmutable",
+    };
+
+    PhpType::DateTime(DateTimeType {
+        class: class.to_string(),
+        format: format.to_string(),
+        exact,
+    })
+}
+
+/// Whether `sql_type` is a `DECIMAL`/`NUMERIC` type, whose PHP type the decimal strategy picks
+pub fn is_decimal_type(sql_type: &DataType, engine: Engine, types: TypeSettings) -> bool {
+    let types = TypeSettings {
+        decimal: DecimalStrategy::BigDecimal,
+        ..types
+    };
+    map_sql_to_php_data_type(sql_type, false, engine, types).is_big_decimal
+}
+
+fn decimal_type(types: TypeSettings) -> PhpType {
+    match types.decimal {
+        DecimalStrategy::String => PhpType::String,
+        DecimalStrategy::Float => PhpType::Float,
+        DecimalStrategy::BigDecimal => PhpType::BigDecimal("\\Brick\\Math\\BigDecimal".to_string()),
+    }
+}
+
+fn unsigned_bigint_type(types: TypeSettings) -> PhpType {
+    match types.unsigned_bigint {
+        UnsignedBigIntStrategy::String => PhpType::String,
+        UnsignedBigIntStrategy::Int => PhpType::Int,
+    }
+}
+
+/// Converts a constant SQL default into a PHP literal of `php_type`, e.g. `'active'` for a
+/// string or `false` for `DEFAULT 0` on a boolean. Anything else, such as `NOW()`, is `None`.
+pub fn php_literal(expr: &Expr, php_type: &PhpType) -> Option<String> {
+    let value = match expr {
+        Expr::Nested(expr) => return php_literal(expr, php_type),
+        // Postgres writes string defaults as `'active'::character varying`
+        Expr::Cast { expr, .. } => return php_literal(expr, php_type),
+        Expr::UnaryOp {
+            op: UnaryOperator::Minus,
+            expr,
+        } => {
+            return php_literal(expr, php_type)
+                .filter(|_| matches!(php_type, PhpType::Int | PhpType::Float))
+                .map(|literal| format!("-{}", literal));
+        }
+        Expr::Value(ValueWithSpan { value, .. }) => value,
+        _ => return None,
+    };
+
+    let text = match value {
+        Value::Number(number, _) => number.to_string(),
+        Value::SingleQuotedString(text) | Value::DoubleQuotedString(text) => text.clone(),
+        Value::Boolean(value) => (if *value { "1" } else { "0" }).to_string(),
+        _ => return None,
+    };
+
+    match php_type {
+        PhpType::Int => text.trim().parse::<i64>().ok().map(|n| n.to_string()),
+        // Rust also reads `inf` and `NaN`, which PHP has no literal for
+        PhpType::Float => text
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|n| n.is_finite())
+            .map(|n| format!("{:?}", n)),
+        PhpType::Bool => match text.trim().to_lowercase().as_str() {
+            "1" | "t" | "true" | "y" | "yes" | "on" => Some("true".to_string()),
+            "0" | "f" | "false" | "n" | "no" | "off" => Some("false".to_string()),
+            _ => None,
+        },
+        PhpType::String => Some(format!(
+            "'{}'",
+            text.replace('\\', "\\\\").replace('\'', "\\'")
+        )),
+        PhpType::Enum(class) => Some(format!("{}::{}", class, php_enum_case(&text))),
+        PhpType::EnumSet(class) => Some(format!(
+            "[{}]",
+            text.split(',')
+                .filter(|value| !value.is_empty())
+                .map(|value| format!("{}::{}", class, php_enum_case(value)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+        _ => None,
+    }
+}
+
+/// Converts a snake_case SQL name into a PascalCase PHP class name
+pub fn php_class_name(name: &str) -> String {
+    let mut result = String::new();
+    let mut capitalize = true;
+
+    for c in name.chars() {
+        if c == '_' {
+            capitalize = true;
+        } else if capitalize {
+            result.push(c.to_ascii_uppercase());
+            capitalize = false;
+        } else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+/// Words PHP does not accept as a class name, compared case-insensitively
+const RESERVED_CLASS_NAMES: &[&str] = &[
+    "__halt_compiler",
+    "abstract",
+    "and",
+    "array",
+    "as",
+    "bool",
+    "break",
+    "callable",
+    "case",
+    "catch",
+    "class",
+    "clone",
+    "const",
+    "continue",
+    "declare",
+    "default",
+    "die",
+    "do",
+    "echo",
+    "else",
+    "elseif",
+    "empty",
+    "enddeclare",
+    "endfor",
+    "endforeach",
+    "endif",
+    "endswitch",
+    "endwhile",
+    "eval",
+    "exit",
+    "extends",
+    "false",
+    "final",
+    "finally",
+    "float",
+    "fn",
+    "for",
+    "foreach",
+    "function",
+    "global",
+    "goto",
+    "if",
+    "implements",
+    "include",
+    "include_once",
+    "instanceof",
+    "insteadof",
+    "int",
+    "interface",
+    "isset",
+    "iterable",
+    "list",
+    "match",
+    "mixed",
+    "namespace",
+    "never",
+    "new",
+    "null",
+    "object",
+    "or",
+    "parent",
+    "print",
+    "private",
+    "protected",
+    "public",
+    "readonly",
+    "require",
+    "require_once",
+    "return",
+    "self",
+    "static",
+    "string",
+    "switch",
+    "throw",
+    "trait",
+    "true",
+    "try",
+    "unset",
+    "use",
+    "var",
+    "void",
+    "while",
+    "xor",
+    "yield",
+];
+
+pub fn is_reserved_class_name(name: &str) -> bool {
+    RESERVED_CLASS_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(name))
+}
+
+/// Name of the enum case for a value, e.g. `InProgress` for `in-progress`
+pub fn php_enum_case(value: &str) -> String {
+    let mut case = String::new();
+    let mut capitalize = true;
+
+    for c in value.chars() {
+        if c.is_ascii_alphanumeric() {
+            if capitalize {
+                case.push(c.to_ascii_uppercase());
+                capitalize = false;
+            } else {
+                case.push(c);
+            }
+        } else {
+            capitalize = true;
+        }
+    }
+
+    match case.chars().next() {
+        None => "Empty".to_string(),
+        Some(first) if first.is_ascii_digit() => format!("Value{}", case),
+        Some(_) => case,
+    }
+}
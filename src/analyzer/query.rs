@@ -0,0 +1,305 @@
+use crate::analyzer::types::{Column, DType, EngineData, Table};
+use crate::analyzer::{ParamInference, PhpDataType, ResultResolver, ScopeRelation};
+use crate::configuration::SqlSettings;
+use crate::diagnostics::{Diagnostic, Diagnostics, into_diagnostics};
+use crate::parser::{QueriesMap, QueryCommand, QuerySqlFileParser};
+use anyhow::Result;
+use serde::Serialize;
+use sqlparser::ast::{DataType, Statement};
+
+pub struct QueryAnalyzer;
+
+/// A query that has been checked against the schema and is ready for generation
+#[derive(Serialize)]
+pub struct AnalyzedQuery {
+    pub name: String,
+    pub command: QueryCommand,
+    /// SQL the generated method prepares
+    pub sql: String,
+    /// The same SQL with `$1`, `$2`, ... in binding order, the way PostgreSQL prepares it
+    pub numbered_sql: String,
+    pub params: Vec<QueryParam>,
+    /// Parameter names in the order they have to be bound to the statement
+    pub bindings: Vec<String>,
+    pub result_columns: Vec<ResultColumn>,
+    /// Table whose entity is returned, if the result has exactly the columns of a table
+    pub result_table: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct QueryParam {
+    pub name: String,
+    pub php_type: PhpDataType,
+    /// PHP literal the parameter defaults to, making it optional
+    pub default_value: Option<String>,
+    /// SQL the database falls back to when the parameter is null, its placeholders are
+    /// wrapped in a `COALESCE`
+    pub database_default: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ResultColumn {
+    pub name: String,
+    pub php_type: PhpDataType,
+    /// Table the column is read from, if it is a plain column reference
+    pub table: Option<String>,
+    /// The default of the column as a PHP literal, if it is a constant
+    pub default_value: Option<String>,
+}
+
+impl QueryAnalyzer {
+    pub fn analyze_queries(
+        config: &SqlSettings,
+        engine_data: &EngineData,
+    ) -> Result<Vec<AnalyzedQuery>> {
+        let queries_dir = &config.queries;
+        println!("Analyzing query files in {}", queries_dir);
+
+        let parser = QuerySqlFileParser::new(config.engine);
+        let query_files = parser.parse_directory(queries_dir)?;
+
+        // Keep going after a broken query so every problem is reported at once
+        let mut analyzed = vec![];
+        let mut names = vec![];
+        let mut diagnostics = Diagnostics::default();
+        for query_file in query_files {
+            for query in &query_file.queries {
+                let location = format!("{}:{}", query_file.path, query.line);
+                match Self::check_unique_name(&mut names, query, location)
+                    .and_then(|_| Self::analyze_query(engine_data, query))
+                {
+                    Ok(query) => analyzed.push(query),
+                    Err(e) => diagnostics.items.extend(
+                        into_diagnostics(e)
+                            .map(|diagnostic| {
+                                diagnostic.in_source(&query_file.path, &query_file.content)
+                            })
+                            .items,
+                    ),
+                }
+            }
+        }
+        diagnostics.into_result()?;
+
+        Ok(analyzed)
+    }
+
+    /// Rejects a query named like one before it, both would generate the same method. PHP
+    /// method names are case-insensitive, so the query names are compared that way too.
+    fn check_unique_name(
+        names: &mut Vec<(String, String)>,
+        query: &QueriesMap,
+        location: String,
+    ) -> Result<()> {
+        if let Some((_, previous)) = names
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(&query.name))
+        {
+            return Err(Diagnostic::at(
+                format!("query {} is already defined at {}", query.name, previous),
+                query.line,
+                "-- name: ".len() + 1,
+                query.name.len(),
+            )
+            .into());
+        }
+        names.push((query.name.clone(), location));
+        Ok(())
+    }
+
+    /// Analyzes a single query. Problems are reported relative to the file the query is in,
+    /// the ones without a location point at the query annotation.
+    pub fn analyze_query(engine_data: &EngineData, query: &QueriesMap) -> Result<AnalyzedQuery> {
+        Self::analyze_statement(engine_data, query).map_err(|e| {
+            into_diagnostics(e)
+                .map(|diagnostic| {
+                    let diagnostic = if diagnostic.line == 0 {
+                        Diagnostic {
+                            line: query.line,
+                            column: "-- name: ".len() + 1,
+                            width: query.name.len(),
+                            ..diagnostic
+                        }
+                    } else {
+                        diagnostic.shift(query.line)
+                    };
+                    diagnostic.context(format!("query {}", query.name))
+                })
+                .into()
+        })
+    }
+
+    fn analyze_statement(engine_data: &EngineData, query: &QueriesMap) -> Result<AnalyzedQuery> {
+        let common_tables = Self::with_common_tables(engine_data, &query.statements[0])?;
+        let statement_data = common_tables.as_ref().unwrap_or(engine_data);
+
+        let (params, bindings) = ParamInference::infer(
+            statement_data,
+            &query.statements[0],
+            &query.placeholders,
+            &query.placeholder_locations,
+        )?;
+
+        let wrap = |placeholder: String, number: usize| {
+            let database_default = bindings
+                .get(number - 1)
+                .and_then(|binding| params.iter().find(|param| &param.name == binding))
+                .and_then(|param| param.database_default.as_ref());
+            match database_default {
+                Some(default) => format!("COALESCE({}, {})", placeholder, default),
+                None => placeholder,
+            }
+        };
+        let offsets = &query.placeholder_offsets;
+        let sql = offsets.replace(&query.sql, |number| wrap("?".to_string(), number));
+        let numbered_sql =
+            offsets.replace_unescaped(&query.sql, |number| wrap(format!("${}", number), number));
+
+        let (result_table, result_columns) = if query.command.returns_rows() {
+            let columns = ResultResolver::resolve(statement_data, &query.statements[0])?;
+            let table = Self::matching_table(engine_data, &columns);
+            (table.map(|table| table.name.clone()), columns)
+        } else {
+            (None, vec![])
+        };
+
+        Ok(AnalyzedQuery {
+            name: query.name.clone(),
+            command: query.command,
+            sql,
+            numbered_sql,
+            params,
+            bindings,
+            result_columns,
+            result_table,
+        })
+    }
+
+    /// The schema as a query with a `WITH` clause sees it, where every common table expression
+    /// is a table with the columns it returns. One can read the ones before it, a recursive
+    /// one its own first SELECT.
+    fn with_common_tables(
+        engine_data: &EngineData,
+        statement: &Statement,
+    ) -> Result<Option<EngineData>> {
+        let Statement::Query(query) = statement else {
+            return Ok(None);
+        };
+        let Some(with) = &query.with else {
+            return Ok(None);
+        };
+
+        let mut statement_data = engine_data.clone();
+        for cte in &with.cte_tables {
+            let aliases = &cte.alias.columns;
+            let columns = if aliases.is_empty() {
+                ResultResolver::resolve(&statement_data, &Statement::Query(cte.query.clone()))?
+            } else {
+                ResultResolver::resolve_renamed(&statement_data, &cte.query)?
+            };
+            if !aliases.is_empty() && aliases.len() != columns.len() {
+                return Err(Diagnostic::at_span(
+                    format!(
+                        "{} names {} columns but its query returns {}",
+                        cte.alias.name,
+                        aliases.len(),
+                        columns.len()
+                    ),
+                    cte.alias.name.span,
+                )
+                .into());
+            }
+
+            let columns = columns
+                .into_iter()
+                .enumerate()
+                .map(|(index, result)| {
+                    Column {
+                        name: aliases
+                            .get(index)
+                            .map_or(result.name, |alias| alias.name.value.clone()),
+                        // Only the PHP type of the column is known
+                        data_type: DType {
+                            sql_type: DataType::Unspecified,
+                            nullable: result.php_type.is_nullable,
+                            php_type: result.php_type,
+                        },
+                        default: None,
+                        generated: None,
+                    }
+                })
+                .collect();
+
+            // Declared first, so it hides a table of the same name
+            statement_data.tables.insert(
+                0,
+                Table {
+                    name: cte.alias.name.value.clone(),
+                    columns,
+                    ..Default::default()
+                },
+            );
+        }
+
+        Ok(Some(statement_data))
+    }
+
+    /// Finds the table a result can be hydrated into: same columns, same order, same types
+    fn matching_table<'a>(
+        engine_data: &'a EngineData,
+        columns: &[ResultColumn],
+    ) -> Option<&'a Table> {
+        engine_data.tables.iter().find(|table| {
+            table.columns.len() == columns.len()
+                && table.columns.iter().zip(columns).all(|(column, result)| {
+                    result.table.as_deref() == Some(table.name.as_str())
+                        && column.name == result.name
+                        && column.data_type.php_type.type_hint == result.php_type.type_hint
+                })
+        })
+    }
+}
+
+impl ResultColumn {
+    pub fn from_column(column: &Column, table: Option<&str>) -> Self {
+        Self {
+            name: column.name.clone(),
+            php_type: column.data_type.php_type.clone(),
+            table: table.map(str::to_string),
+            default_value: column
+                .default
+                .as_ref()
+                .and_then(|default| default.php_value.clone()),
+        }
+    }
+
+    /// A column read through a relation, nullable if the relation is outer joined
+    pub fn from_relation(relation: &ScopeRelation, column: &Column) -> Self {
+        let mut result = Self::from_column(column, Some(&relation.table.name));
+        if relation.nullable {
+            result.php_type = PhpDataType::new(result.php_type.php_type, true);
+        }
+        result
+    }
+}
+
+pub fn process_query_file(content: String, engine_data: &EngineData) -> Result<Vec<AnalyzedQuery>> {
+    let parser = QuerySqlFileParser::new(engine_data.engine);
+    let queries = parser.parse_content(&content)?;
+
+    let mut analyzed = vec![];
+    let mut names = vec![];
+    let mut diagnostics = Diagnostics::default();
+    for query in &queries {
+        let location = format!("line {}", query.line);
+        match QueryAnalyzer::check_unique_name(&mut names, query, location)
+            .and_then(|_| QueryAnalyzer::analyze_query(engine_data, query))
+        {
+            Ok(query) => analyzed.push(query),
+            Err(e) => diagnostics.push_error(e),
+        }
+    }
+    diagnostics.into_result()?;
+
+    Ok(analyzed)
+}
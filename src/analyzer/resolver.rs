@@ -0,0 +1,461 @@
+use crate::analyzer::scope::{Scope, object_name_last};
+use crate::analyzer::types::EngineData;
+use crate::analyzer::{
+    PhpDataType, PhpType, ResultColumn, is_decimal_type, map_sql_to_php_data_type,
+};
+use crate::configuration::Engine;
+use crate::diagnostics::Diagnostic;
+use anyhow::{Result, anyhow};
+use sqlparser::ast::*;
+
+/// Works out the columns, and their PHP types, a statement returns
+pub struct ResultResolver<'a> {
+    engine_data: &'a EngineData,
+    /// Set when the columns are named by the caller, so expressions need no alias
+    renamed: bool,
+}
+
+impl<'a> ResultResolver<'a> {
+    pub fn resolve(
+        engine_data: &'a EngineData,
+        statement: &Statement,
+    ) -> Result<Vec<ResultColumn>> {
+        let resolver = Self {
+            engine_data,
+            renamed: false,
+        };
+        let columns = resolver.resolve_statement(statement, None)?;
+
+        for (index, column) in columns.iter().enumerate() {
+            if columns[..index]
+                .iter()
+                .any(|other| other.name == column.name)
+            {
+                return Err(anyhow!(
+                    "column {} is returned more than once, give it a unique name with AS",
+                    column.name
+                ));
+            }
+        }
+
+        Ok(columns)
+    }
+
+    /// Works out the columns of a query whose names are given separately, like those of a
+    /// common table expression with a column list
+    pub fn resolve_renamed(
+        engine_data: &'a EngineData,
+        query: &Query,
+    ) -> Result<Vec<ResultColumn>> {
+        let resolver = Self {
+            engine_data,
+            renamed: true,
+        };
+        resolver.resolve_query(query, None)
+    }
+
+    fn resolve_statement(
+        &self,
+        statement: &Statement,
+        parent: Option<&Scope>,
+    ) -> Result<Vec<ResultColumn>> {
+        match statement {
+            Statement::Query(query) => self.resolve_query(query, parent),
+            Statement::Insert(Insert {
+                table: TableObject::TableName(name),
+                returning: Some(returning),
+                ..
+            }) => {
+                let mut scope = Scope::new(parent);
+                scope.add_table(self.engine_data, name, None)?;
+                self.resolve_projection(returning, &scope)
+            }
+            Statement::Update {
+                table,
+                returning: Some(returning),
+                ..
+            } => {
+                let mut scope = Scope::new(parent);
+                scope.add_table_with_joins(self.engine_data, table)?;
+                self.resolve_projection(returning, &scope)
+            }
+            Statement::Delete(Delete {
+                from: FromTable::WithFromKeyword(from) | FromTable::WithoutKeyword(from),
+                returning: Some(returning),
+                ..
+            }) => {
+                let mut scope = Scope::new(parent);
+                for table in from {
+                    scope.add_table_with_joins(self.engine_data, table)?;
+                }
+                self.resolve_projection(returning, &scope)
+            }
+            _ => Err(anyhow!("statement does not return rows")),
+        }
+    }
+
+    fn resolve_query(&self, query: &Query, parent: Option<&Scope>) -> Result<Vec<ResultColumn>> {
+        self.resolve_set_expr(&query.body, parent)
+    }
+
+    fn resolve_set_expr(
+        &self,
+        body: &SetExpr,
+        parent: Option<&Scope>,
+    ) -> Result<Vec<ResultColumn>> {
+        match body {
+            SetExpr::Select(select) => {
+                let mut scope = Scope::new(parent);
+                for from in &select.from {
+                    scope.add_table_with_joins(self.engine_data, from)?;
+                }
+                self.resolve_projection(&select.projection, &scope)
+            }
+            SetExpr::Query(query) => self.resolve_query(query, parent),
+            // The first SELECT of a UNION names the columns
+            SetExpr::SetOperation { left, .. } => self.resolve_set_expr(left, parent),
+            _ => Err(anyhow!("unsupported query body {}", body)),
+        }
+    }
+
+    fn resolve_projection(&self, items: &[SelectItem], scope: &Scope) -> Result<Vec<ResultColumn>> {
+        let mut columns = vec![];
+
+        for item in items {
+            match item {
+                SelectItem::Wildcard(_) => {
+                    if !scope.opaque.is_empty() {
+                        return Err(anyhow!("`*` cannot be expanded over a derived table"));
+                    }
+                    for relation in &scope.relations {
+                        columns.extend(
+                            relation
+                                .table
+                                .columns
+                                .iter()
+                                .map(|column| ResultColumn::from_relation(relation, column)),
+                        );
+                    }
+                }
+                SelectItem::QualifiedWildcard(
+                    SelectItemQualifiedWildcardKind::ObjectName(name),
+                    _,
+                ) => {
+                    let qualifier = object_name_last(name);
+                    let relation = scope.relation(&qualifier).ok_or_else(|| {
+                        Diagnostic::at_span(
+                            format!("unknown table or alias {}", qualifier),
+                            name.span(),
+                        )
+                    })?;
+                    columns.extend(
+                        relation
+                            .table
+                            .columns
+                            .iter()
+                            .map(|column| ResultColumn::from_relation(relation, column)),
+                    );
+                }
+                SelectItem::QualifiedWildcard(SelectItemQualifiedWildcardKind::Expr(expr), _) => {
+                    return Err(Diagnostic::at_span(
+                        format!("unsupported projection {}.*", expr),
+                        expr.span(),
+                    )
+                    .into());
+                }
+                SelectItem::UnnamedExpr(expr) => {
+                    let name = match Self::column_name(expr) {
+                        Err(_) if self.renamed => expr.to_string(),
+                        name => name?,
+                    };
+                    columns.push(self.resolve_expr(name, expr, scope)?);
+                }
+                SelectItem::ExprWithAlias { expr, alias } => {
+                    columns.push(self.resolve_expr(alias.value.clone(), expr, scope)?);
+                }
+            }
+        }
+
+        Ok(columns)
+    }
+
+    fn resolve_expr(&self, name: String, expr: &Expr, scope: &Scope) -> Result<ResultColumn> {
+        let table = scope
+            .lookup(expr)
+            .map(|(relation, _)| relation.table.name.clone());
+
+        Ok(ResultColumn {
+            name,
+            php_type: self.expr_type(expr, scope)?,
+            table,
+            default_value: None,
+        })
+    }
+
+    /// Name PDO will use as the array key of an unaliased expression. Engines disagree on
+    /// how computed expressions are labelled, so those have to be aliased.
+    fn column_name(expr: &Expr) -> Result<String> {
+        match expr {
+            Expr::Identifier(ident) => Ok(ident.value.clone()),
+            Expr::CompoundIdentifier(idents) => idents
+                .last()
+                .map(|ident| ident.value.clone())
+                .ok_or_else(|| anyhow!("empty column reference")),
+            Expr::Nested(inner) => Self::column_name(inner),
+            _ => Err(Diagnostic::at_span(
+                format!("expression {} needs a column name, add AS <name>", expr),
+                expr.span(),
+            )
+            .into()),
+        }
+    }
+
+    fn expr_type(&self, expr: &Expr, scope: &Scope) -> Result<PhpDataType> {
+        let php_type = match expr {
+            Expr::Identifier(_) | Expr::CompoundIdentifier(_) => {
+                return match scope.lookup(expr) {
+                    Some((relation, column)) if relation.nullable => Ok(PhpDataType::new(
+                        column.data_type.php_type.php_type.clone(),
+                        true,
+                    )),
+                    Some((_, column)) => Ok(column.data_type.php_type.clone()),
+                    None if scope.is_opaque(expr) => Ok(PhpDataType::new(PhpType::Mixed, true)),
+                    None => Err(Diagnostic::at_span(
+                        format!("unknown column {}", expr),
+                        expr.span(),
+                    )
+                    .into()),
+                };
+            }
+            Expr::Value(value) => {
+                return Ok(match &value.value {
+                    Value::Number(number, _) if number.contains(['.', 'e', 'E']) => {
+                        PhpDataType::new(PhpType::Float, false)
+                    }
+                    Value::Number(_, _) => PhpDataType::new(PhpType::Int, false),
+                    Value::Boolean(_) => PhpDataType::new(PhpType::Bool, false),
+                    Value::Null | Value::Placeholder(_) => PhpDataType::new(PhpType::Mixed, true),
+                    _ => PhpDataType::new(PhpType::String, false),
+                });
+            }
+            Expr::Nested(inner) => return self.expr_type(inner, scope),
+            Expr::Cast {
+                expr, data_type, ..
+            } => {
+                let inner = self.expr_type(expr, scope)?;
+                return Ok(map_sql_to_php_data_type(
+                    data_type,
+                    inner.is_nullable,
+                    self.engine_data.engine,
+                    self.engine_data.types,
+                ));
+            }
+            Expr::BinaryOp { left, op, right } => {
+                let left = self.expr_type(left, scope)?;
+                let right = self.expr_type(right, scope)?;
+                let nullable = left.is_nullable || right.is_nullable;
+                let php_type = match op {
+                    BinaryOperator::Plus
+                    | BinaryOperator::Minus
+                    | BinaryOperator::Multiply
+                    | BinaryOperator::Modulo => {
+                        if left.php_type == PhpType::Int && right.php_type == PhpType::Int {
+                            PhpType::Int
+                        } else {
+                            PhpType::Float
+                        }
+                    }
+                    BinaryOperator::Divide => PhpType::Float,
+                    BinaryOperator::StringConcat => PhpType::String,
+                    _ => PhpType::Bool,
+                };
+                return Ok(PhpDataType::new(php_type, nullable));
+            }
+            Expr::UnaryOp { op, expr } => {
+                let inner = self.expr_type(expr, scope)?;
+                return Ok(match op {
+                    UnaryOperator::Not => PhpDataType::new(PhpType::Bool, inner.is_nullable),
+                    _ => inner,
+                });
+            }
+            Expr::IsNull(_)
+            | Expr::IsNotNull(_)
+            | Expr::IsTrue(_)
+            | Expr::IsNotTrue(_)
+            | Expr::IsFalse(_)
+            | Expr::IsNotFalse(_)
+            | Expr::IsDistinctFrom(_, _)
+            | Expr::IsNotDistinctFrom(_, _)
+            | Expr::Exists { .. } => PhpType::Bool,
+            Expr::InList { expr, .. }
+            | Expr::InSubquery { expr, .. }
+            | Expr::Between { expr, .. }
+            | Expr::Like { expr, .. }
+            | Expr::ILike { expr, .. } => {
+                let inner = self.expr_type(expr, scope)?;
+                return Ok(PhpDataType::new(PhpType::Bool, inner.is_nullable));
+            }
+            Expr::Case {
+                conditions,
+                else_result,
+                ..
+            } => {
+                let mut results = conditions
+                    .iter()
+                    .map(|when| self.expr_type(&when.result, scope))
+                    .collect::<Result<Vec<_>>>()?;
+                match else_result {
+                    Some(else_result) => results.push(self.expr_type(else_result, scope)?),
+                    None => results.push(PhpDataType::new(PhpType::Mixed, true)),
+                }
+                return Ok(Self::common_type(&results));
+            }
+            Expr::Subquery(query) => {
+                let columns = self.resolve_query(query, Some(scope))?;
+                let first = columns
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| anyhow!("subquery does not return a column"))?;
+                return Ok(PhpDataType::new(first.php_type.php_type, true));
+            }
+            Expr::Function(function) => return self.function_type(function, scope),
+            Expr::Substring { expr, .. } | Expr::Trim { expr, .. } => {
+                let inner = self.expr_type(expr, scope)?;
+                return Ok(PhpDataType::new(PhpType::String, inner.is_nullable));
+            }
+            Expr::Extract { expr, .. } | Expr::Position { r#in: expr, .. } => {
+                let inner = self.expr_type(expr, scope)?;
+                return Ok(PhpDataType::new(PhpType::Int, inner.is_nullable));
+            }
+            Expr::Ceil { expr, .. } | Expr::Floor { expr, .. } => {
+                let inner = self.expr_type(expr, scope)?;
+                return Ok(PhpDataType::new(PhpType::Int, inner.is_nullable));
+            }
+            _ => return Ok(PhpDataType::new(PhpType::Mixed, true)),
+        };
+
+        Ok(PhpDataType::new(php_type, false))
+    }
+
+    fn function_type(&self, function: &Function, scope: &Scope) -> Result<PhpDataType> {
+        let name = object_name_last(&function.name).to_uppercase();
+
+        let mut exprs = vec![];
+        if let FunctionArguments::List(list) = &function.args {
+            for arg in &list.args {
+                match arg {
+                    FunctionArg::Unnamed(FunctionArgExpr::Expr(expr))
+                    | FunctionArg::Named {
+                        arg: FunctionArgExpr::Expr(expr),
+                        ..
+                    } => exprs.push(expr),
+                    _ => {}
+                }
+            }
+        }
+        let args = exprs
+            .iter()
+            .map(|expr| self.expr_type(expr, scope))
+            .collect::<Result<Vec<_>>>()?;
+        let any_nullable = args.iter().any(|arg| arg.is_nullable);
+        let first = args.first().cloned();
+        // The sum and average of decimals are decimals, which keep the type of the strategy
+        let of_decimal = exprs
+            .first()
+            .is_some_and(|expr| self.is_decimal(expr, scope));
+
+        Ok(match name.as_str() {
+            "COUNT" => PhpDataType::new(PhpType::Int, false),
+            "SUM" | "AVG" if of_decimal => PhpDataType::new(args[0].php_type.clone(), true),
+            "SUM" => match first {
+                Some(arg) if arg.php_type == PhpType::Int => PhpDataType::new(PhpType::Int, true),
+                _ => PhpDataType::new(PhpType::Float, true),
+            },
+            "AVG" => PhpDataType::new(PhpType::Float, true),
+            "MIN" | "MAX" => match first {
+                Some(arg) => PhpDataType::new(arg.php_type, true),
+                None => PhpDataType::new(PhpType::Mixed, true),
+            },
+            "COALESCE" | "IFNULL" | "NVL" => {
+                let php_type = args
+                    .iter()
+                    .map(|arg| arg.php_type.clone())
+                    .find(|php_type| *php_type != PhpType::Mixed)
+                    .unwrap_or(PhpType::Mixed);
+                PhpDataType::new(php_type, args.iter().all(|arg| arg.is_nullable))
+            }
+            "NULLIF" => match first {
+                Some(arg) => PhpDataType::new(arg.php_type, true),
+                None => PhpDataType::new(PhpType::Mixed, true),
+            },
+            "CONCAT" | "LOWER" | "UPPER" | "TRIM" | "LTRIM" | "RTRIM" | "REPLACE" | "SUBSTR"
+            | "SUBSTRING" | "LEFT" | "RIGHT" | "LPAD" | "RPAD" | "REVERSE" | "MD5" | "SHA1"
+            | "SHA2" | "HEX" | "UUID" => PhpDataType::new(PhpType::String, any_nullable),
+            "CONCAT_WS" | "GROUP_CONCAT" | "STRING_AGG" => PhpDataType::new(PhpType::String, true),
+            "LENGTH" | "CHAR_LENGTH" | "CHARACTER_LENGTH" | "OCTET_LENGTH" | "ROUND" | "ABS"
+            | "SIGN" | "YEAR" | "MONTH" | "DAY" | "HOUR" | "MINUTE" | "SECOND" => {
+                match (name.as_str(), first) {
+                    ("ROUND" | "ABS", Some(arg)) if arg.php_type == PhpType::Float => {
+                        PhpDataType::new(PhpType::Float, any_nullable)
+                    }
+                    _ => PhpDataType::new(PhpType::Int, any_nullable),
+                }
+            }
+            "NOW" | "CURRENT_TIMESTAMP" | "CURRENT_DATE" | "CURRENT_TIME" | "LOCALTIMESTAMP"
+            | "SYSDATE" | "UTC_TIMESTAMP" => {
+                let engine = self.engine_data.engine;
+                // PostgreSQL's current timestamps carry a time zone, LOCALTIMESTAMP does not
+                let sql_type = match name.as_str() {
+                    "CURRENT_DATE" => DataType::Date,
+                    "CURRENT_TIME" => DataType::Time(None, TimezoneInfo::None),
+                    "NOW" | "CURRENT_TIMESTAMP" if engine == Engine::PostgreSql => {
+                        DataType::Timestamp(None, TimezoneInfo::WithTimeZone)
+                    }
+                    _ => DataType::Datetime(None),
+                };
+                map_sql_to_php_data_type(&sql_type, false, engine, self.engine_data.types)
+            }
+            "LAST_INSERT_ID" | "ROW_NUMBER" | "RANK" | "DENSE_RANK" => {
+                PhpDataType::new(PhpType::Int, false)
+            }
+            _ => PhpDataType::new(PhpType::Mixed, true),
+        })
+    }
+
+    /// Type shared by all branches of a CASE, nullable if any branch is
+    /// Whether `expr` is a column or cast of a `DECIMAL`/`NUMERIC` type
+    fn is_decimal(&self, expr: &Expr, scope: &Scope) -> bool {
+        let sql_type = match expr {
+            Expr::Nested(inner) => return self.is_decimal(inner, scope),
+            Expr::Cast { data_type, .. } => data_type,
+            _ => match scope.resolve_column(expr) {
+                Some(column) => &column.data_type.sql_type,
+                None => return false,
+            },
+        };
+        is_decimal_type(sql_type, self.engine_data.engine, self.engine_data.types)
+    }
+
+    fn common_type(types: &[PhpDataType]) -> PhpDataType {
+        let nullable = types.iter().any(|php_type| php_type.is_nullable);
+        let known: Vec<&PhpType> = types
+            .iter()
+            .map(|php_type| &php_type.php_type)
+            .filter(|php_type| **php_type != PhpType::Mixed)
+            .collect();
+
+        let php_type = match known.first() {
+            Some(first) if known.iter().all(|php_type| php_type == first) => (*first).clone(),
+            Some(_)
+                if known
+                    .iter()
+                    .all(|php_type| matches!(php_type, PhpType::Int | PhpType::Float)) =>
+            {
+                PhpType::Float
+            }
+            _ => PhpType::Mixed,
+        };
+
+        PhpDataType::new(php_type, nullable)
+    }
+}
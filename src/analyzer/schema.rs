@@ -1,27 +1,39 @@
-use crate::analyzer::map_sql_to_php_data_type;
-use crate::analyzer::types::{Column, DType, EngineData, Table};
-use crate::configuration::Settings;
+use crate::analyzer::object_name_last;
+use crate::analyzer::types::{
+    Column, ColumnDefault, DType, EngineData, EnumType, Generated, Table,
+};
+use crate::configuration::{Engine, SqlSettings, TypeSettings};
+use crate::diagnostics::{Diagnostic, Diagnostics, into_diagnostics};
 use crate::parser::SqlFileParser;
-use anyhow::{Result, anyhow};
+use anyhow::Result;
 use sqlparser::ast::*;
 
 pub struct SchemaAnalyzer;
 
 impl SchemaAnalyzer {
-    pub fn analyze_schema(config: &Settings) -> Result<EngineData> {
-        let schema_dir = &config.sql.schemas;
-        println!("Analyzing schema files in {}", schema_dir);
+    pub fn analyze_schema(config: &SqlSettings) -> Result<EngineData> {
+        let schemas = &config.schemas;
+        println!("Analyzing schema files in {}", schemas.join(", "));
 
         // Use the parser to parse SQL files
-        let parser = SqlFileParser::default();
-        let sql_files = parser.parse_directory(schema_dir)?;
+        let parser = SqlFileParser::new(config.engine);
+        let sql_files = parser.parse_paths(schemas)?;
 
-        let mut engine_data = EngineData { tables: vec![] };
+        let mut engine_data = EngineData::new(config.engine);
+        engine_data.types = config.types;
 
-        // Analyze each SQL file
+        // Analyze each SQL file, collecting the problems of all of them
+        let mut diagnostics = Diagnostics::default();
         for sql_file in sql_files {
-            Self::analyze_sql_statements(&mut engine_data, sql_file.statements)?;
+            if let Err(e) = Self::analyze_sql_statements(&mut engine_data, sql_file.statements) {
+                diagnostics.items.extend(
+                    into_diagnostics(e)
+                        .map(|diagnostic| diagnostic.in_source(&sql_file.path, &sql_file.content))
+                        .items,
+                );
+            }
         }
+        diagnostics.into_result()?;
 
         Ok(engine_data)
     }
@@ -30,48 +42,155 @@ impl SchemaAnalyzer {
         engine_data: &mut EngineData,
         statements: Vec<Statement>,
     ) -> Result<()> {
+        let engine = engine_data.engine;
+        let mut diagnostics = Diagnostics::default();
+
         for statement in statements {
             match statement {
                 Statement::CreateTable(create_table) => {
+                    let table_name = object_name_last(&create_table.name);
                     let mut columns: Vec<Column> = vec![];
-                    for column_def in create_table.columns {
-                        columns.push(Self::analyze_column_def(&column_def));
+                    for column_def in &create_table.columns {
+                        match Self::analyze_column_def(
+                            column_def,
+                            &engine_data.enums,
+                            &table_name,
+                            engine,
+                            engine_data.types,
+                            create_table.without_rowid,
+                        ) {
+                            Ok(column) => columns.push(column),
+                            Err(e) => diagnostics.push_error(e),
+                        }
                     }
                     let table = Table {
-                        name: create_table.name.to_string(),
+                        name: table_name,
                         columns,
+                        ..Default::default()
                     };
                     engine_data.tables.push(table);
+
+                    // Constraints are added once the table exists so keys can reference it
+                    let table_index = engine_data.tables.len() - 1;
+                    for column_def in &create_table.columns {
+                        if let Err(e) =
+                            Self::add_column_constraints(engine_data, table_index, column_def)
+                        {
+                            diagnostics.push_error(e);
+                        }
+                    }
+                    for constraint in create_table.constraints {
+                        if let Err(e) = Self::add_table_constraint(
+                            engine_data,
+                            table_index,
+                            constraint,
+                            create_table.without_rowid,
+                        ) {
+                            diagnostics.push_error(e);
+                        }
+                    }
+                }
+                Statement::CreateType {
+                    name,
+                    representation,
+                } => {
+                    if let Err(e) = Self::create_type(engine_data, &name, representation) {
+                        diagnostics.push_error(e);
+                    }
+                }
+                Statement::AlterType(alter_type) => {
+                    if let Err(e) = Self::alter_type(engine_data, alter_type) {
+                        diagnostics.push_error(e);
+                    }
+                }
+                Statement::CreateIndex(create_index) => {
+                    if let Err(e) = Self::create_index(engine_data, create_index) {
+                        diagnostics.push_error(e);
+                    }
                 }
                 Statement::AlterTable {
                     name, operations, ..
                 } => {
-                    let table_name = name.to_string();
-                    let table_index = engine_data
-                        .tables
-                        .iter()
-                        .position(|existing_table| existing_table.name.eq(&table_name));
-
-                    if let Some(table_index) = table_index {
-                        for operation in operations {
-                            match operation {
-                                AlterTableOperation::AddColumn { column_def, .. } => {
-                                    // Add the column to the existing table
-                                    engine_data.tables[table_index]
-                                        .columns
-                                        .push(Self::analyze_column_def(&column_def));
-                                }
-                                _ => {
-                                    // todo - add other operations like DropColumn, DropConstraint, AddConstraint,...
-                                    println!("Unhandled alter table operation: {:?}", operation);
+                    let table_name = object_name_last(&name);
+                    let Some(table_index) = engine_data.table_index(&table_name) else {
+                        diagnostics.push(Diagnostic::at_span(
+                            format!("Alter table references unknown table: {}", table_name),
+                            name.span(),
+                        ));
+                        continue;
+                    };
+
+                    for operation in operations {
+                        if let Err(e) =
+                            Self::alter_table(engine_data, table_index, operation, engine)
+                        {
+                            diagnostics.push_error(e);
+                        }
+                    }
+                }
+                Statement::RenameTable(renames) => {
+                    for rename in renames {
+                        match engine_data.table_index(&object_name_last(&rename.old_name)) {
+                            Some(table_index) => {
+                                if let Err(e) =
+                                    Self::rename_table(engine_data, table_index, &rename.new_name)
+                                {
+                                    diagnostics.push_error(e);
                                 }
                             }
+                            None => diagnostics.push(Diagnostic::at_span(
+                                format!(
+                                    "Rename table references unknown table: {}",
+                                    rename.old_name
+                                ),
+                                rename.old_name.span(),
+                            )),
+                        }
+                    }
+                }
+                Statement::Drop {
+                    object_type: ObjectType::Table,
+                    if_exists,
+                    names,
+                    ..
+                } => {
+                    for name in names {
+                        match engine_data.table_index(&object_name_last(&name)) {
+                            Some(table_index) => {
+                                let table = engine_data.tables.remove(table_index);
+                                engine_data.drop_table_references(&table.name);
+                            }
+                            None if if_exists => {}
+                            None => diagnostics.push(Diagnostic::at_span(
+                                format!("Drop table references unknown table: {}", name),
+                                name.span(),
+                            )),
+                        }
+                    }
+                }
+                Statement::Drop {
+                    object_type: ObjectType::Type,
+                    if_exists,
+                    names,
+                    cascade,
+                    ..
+                } => {
+                    for name in names {
+                        if let Err(e) = Self::drop_type(engine_data, &name, if_exists, cascade) {
+                            diagnostics.push_error(e);
+                        }
+                    }
+                }
+                Statement::Drop {
+                    object_type: ObjectType::Index,
+                    if_exists,
+                    names,
+                    ..
+                } => {
+                    for name in names {
+                        if let Err(e) = Self::drop_index(engine_data, &name, if_exists) {
+                            diagnostics.push_error(e);
                         }
-                    } else {
-                        return Err(anyhow!(
-                            "Alter table references unknown table: {}",
-                            table_name
-                        ));
                     }
                 }
                 _ => {
@@ -80,42 +199,366 @@ impl SchemaAnalyzer {
             }
         }
 
+        diagnostics.into_result()
+    }
+
+    /// Applies a single `ALTER TABLE` operation to the table at `table_index`
+    fn alter_table(
+        engine_data: &mut EngineData,
+        table_index: usize,
+        operation: AlterTableOperation,
+        engine: Engine,
+    ) -> Result<()> {
+        let operation = match operation {
+            AlterTableOperation::AddConstraint(constraint) => {
+                return Self::add_table_constraint(engine_data, table_index, constraint, false);
+            }
+            operation => operation,
+        };
+
+        let table = &mut engine_data.tables[table_index];
+        let unknown_column = |table: &Table, column: &Ident| {
+            Diagnostic::at_span(
+                format!("table {} has no column {}", table.name, column),
+                column.span,
+            )
+        };
+
+        match operation {
+            AlterTableOperation::AddColumn {
+                if_not_exists,
+                column_def,
+                column_position,
+                ..
+            } => {
+                if table.column_index(&column_def.name.value).is_some() {
+                    if if_not_exists {
+                        return Ok(());
+                    }
+                    return Err(Diagnostic::at_span(
+                        format!(
+                            "table {} already has a column {}",
+                            table.name, column_def.name
+                        ),
+                        column_def.name.span,
+                    )
+                    .into());
+                }
+                let column = Self::analyze_column_def(
+                    &column_def,
+                    &engine_data.enums,
+                    &table.name,
+                    engine,
+                    engine_data.types,
+                    false,
+                )?;
+                Self::place_column(table, column, column_position.as_ref(), None)?;
+                Self::add_column_constraints(engine_data, table_index, &column_def)?;
+            }
+            AlterTableOperation::DropColumn {
+                column_name,
+                if_exists,
+                ..
+            } => match table.column_index(&column_name.value) {
+                Some(index) => {
+                    table.columns.remove(index);
+                    table.drop_column_references(&column_name.value);
+                }
+                None if if_exists => {}
+                None => return Err(unknown_column(table, &column_name).into()),
+            },
+            AlterTableOperation::RenameColumn {
+                old_column_name,
+                new_column_name,
+            } => {
+                let index = table
+                    .column_index(&old_column_name.value)
+                    .ok_or_else(|| unknown_column(table, &old_column_name))?;
+                engine_data.rename_column_references(
+                    table_index,
+                    &old_column_name.value,
+                    &new_column_name.value,
+                );
+                engine_data.tables[table_index].columns[index].name = new_column_name.value;
+            }
+            // MySQL's CHANGE and MODIFY replace the whole column definition
+            AlterTableOperation::ChangeColumn {
+                old_name,
+                new_name,
+                data_type,
+                options,
+                column_position,
+            } => {
+                let index = table
+                    .column_index(&old_name.value)
+                    .ok_or_else(|| unknown_column(table, &old_name))?;
+                engine_data.rename_column_references(table_index, &old_name.value, &new_name.value);
+                let column_def = Self::column_def(new_name, data_type, options);
+                Self::replace_column(engine_data, table_index, index, column_def, column_position)?;
+            }
+            AlterTableOperation::ModifyColumn {
+                col_name,
+                data_type,
+                options,
+                column_position,
+            } => {
+                let index = table
+                    .column_index(&col_name.value)
+                    .ok_or_else(|| unknown_column(table, &col_name))?;
+                let column_def = Self::column_def(col_name, data_type, options);
+                Self::replace_column(engine_data, table_index, index, column_def, column_position)?;
+            }
+            AlterTableOperation::AlterColumn { column_name, op } => {
+                let index = table
+                    .column_index(&column_name.value)
+                    .ok_or_else(|| unknown_column(table, &column_name))?;
+                let table_name = table.name.clone();
+                let column = &mut table.columns[index];
+                match op {
+                    AlterColumnOperation::SetNotNull => column.set_nullable(false),
+                    AlterColumnOperation::DropNotNull => column.set_nullable(true),
+                    AlterColumnOperation::SetDataType { data_type, .. } => {
+                        column.retype(DType::for_column(
+                            data_type,
+                            column.data_type.nullable,
+                            engine,
+                            engine_data.types,
+                            &engine_data.enums,
+                            &table_name,
+                            &column.name,
+                        ));
+                    }
+                    AlterColumnOperation::SetDefault { value } => {
+                        column.default = Some(ColumnDefault::new(value, &column.data_type));
+                    }
+                    AlterColumnOperation::DropDefault => column.default = None,
+                    AlterColumnOperation::AddGenerated { .. } => {
+                        column.generated = Some(Generated::Identity);
+                    }
+                }
+            }
+            // Constraints without a name were named by the database, those are left alone
+            AlterTableOperation::DropConstraint { name, .. } => {
+                if !table.drop_constraint(&name.value) {
+                    table.drop_index(&name.value);
+                }
+            }
+            AlterTableOperation::DropPrimaryKey => table.primary_key.clear(),
+            AlterTableOperation::RenameConstraint { old_name, new_name } => {
+                table.rename_constraint(&old_name.value, &new_name.value);
+            }
+            AlterTableOperation::RenameTable { table_name } => {
+                Self::rename_table(engine_data, table_index, &table_name)?;
+            }
+            _ => {
+                println!("Unhandled alter table operation: {:?}", operation);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn rename_table(
+        engine_data: &mut EngineData,
+        table_index: usize,
+        new_name: &ObjectName,
+    ) -> Result<()> {
+        let name = object_name_last(new_name);
+        if engine_data.table_index(&name).is_some() {
+            return Err(Diagnostic::at_span(
+                format!("cannot rename to {}, the table already exists", name),
+                new_name.span(),
+            )
+            .into());
+        }
+        let old_name = std::mem::replace(&mut engine_data.tables[table_index].name, name.clone());
+        engine_data.rename_table_references(&old_name, &name);
+        Ok(())
+    }
+
+    /// Swaps the column at `index` for a new definition. The primary key outlives the old
+    /// definition, so its columns stay NOT NULL even when the new one leaves that out.
+    fn replace_column(
+        engine_data: &mut EngineData,
+        table_index: usize,
+        index: usize,
+        column_def: ColumnDef,
+        position: Option<MySQLColumnPosition>,
+    ) -> Result<()> {
+        let engine = engine_data.engine;
+        let table = &mut engine_data.tables[table_index];
+        let column = Self::analyze_column_def(
+            &column_def,
+            &engine_data.enums,
+            &table.name,
+            engine,
+            engine_data.types,
+            false,
+        )?;
+        Self::place_column(table, column, position.as_ref(), Some(index))?;
+        Self::apply_table_primary_key(table, engine, false);
+        Self::add_column_constraints(engine_data, table_index, &column_def)
+    }
+
+    /// Puts a column in a table, replacing the one at `replace` and honouring MySQL's
+    /// `FIRST` and `AFTER <column>`
+    fn place_column(
+        table: &mut Table,
+        column: Column,
+        position: Option<&MySQLColumnPosition>,
+        replace: Option<usize>,
+    ) -> Result<()> {
+        let index = match position {
+            None => {
+                match replace {
+                    Some(index) => table.columns[index] = column,
+                    None => table.columns.push(column),
+                }
+                return Ok(());
+            }
+            Some(MySQLColumnPosition::First) => 0,
+            Some(MySQLColumnPosition::After(after)) => {
+                table.column_index(&after.value).ok_or_else(|| {
+                    Diagnostic::at_span(
+                        format!("table {} has no column {}", table.name, after),
+                        after.span,
+                    )
+                })? + 1
+            }
+        };
+
+        match replace {
+            Some(replaced) => {
+                table.columns.remove(replaced);
+                let index = if replaced < index { index - 1 } else { index };
+                table.columns.insert(index, column);
+            }
+            None => table.columns.insert(index, column),
+        }
         Ok(())
     }
 
-    fn analyze_column_def(column_def: &ColumnDef) -> Column {
+    fn column_def(name: Ident, data_type: DataType, options: Vec<ColumnOption>) -> ColumnDef {
+        ColumnDef {
+            name,
+            data_type,
+            options: options
+                .into_iter()
+                .map(|option| ColumnOptionDef { name: None, option })
+                .collect(),
+        }
+    }
+
+    fn analyze_column_def(
+        column_def: &ColumnDef,
+        enums: &[EnumType],
+        table_name: &str,
+        engine: Engine,
+        types: TypeSettings,
+        without_rowid: bool,
+    ) -> Result<Column> {
         let mut is_nullable = true;
+        let mut is_primary = false;
+        let mut is_autoincrement = false;
+        let mut default = None;
+        let mut generated = None;
 
         for opt in &column_def.options {
-            match opt.option {
+            match &opt.option {
                 ColumnOption::NotNull => is_nullable = false,
-                ColumnOption::Unique { is_primary, .. } => {
-                    if is_primary {
-                        is_nullable = false
-                    }
+                ColumnOption::Unique {
+                    is_primary: true, ..
+                } => is_primary = true,
+                ColumnOption::Default(expr) => default = Some(expr.clone()),
+                // SQLite's AUTOINCREMENT and MySQL's AUTO_INCREMENT
+                ColumnOption::DialectSpecific(tokens) => {
+                    is_autoincrement |= tokens.iter().any(|token| {
+                        let token = token.to_string();
+                        token.eq_ignore_ascii_case("AUTOINCREMENT")
+                            || token.eq_ignore_ascii_case("AUTO_INCREMENT")
+                    });
                 }
-                _ => {
-                    // todo - add options later on when we're defining relations, uniques, etc
+                ColumnOption::Generated {
+                    generation_expr, ..
+                } => {
+                    generated = Some(match generation_expr {
+                        Some(expr) => Generated::Computed(expr.to_string()),
+                        None => Generated::Identity,
+                    });
                 }
+                // Keys and constraints are recorded by `add_column_constraints`
+                _ => {}
             }
         }
 
-        Column {
-            name: column_def.name.to_string(),
-            data_type: DType {
-                php_type: map_sql_to_php_data_type(&column_def.data_type, is_nullable),
-                sql_type: column_def.data_type.clone(),
-                nullable: is_nullable,
-            },
+        // SQLite lets a PRIMARY KEY hold NULL unless it is an alias of the rowid, which only
+        // a column declared exactly INTEGER is, or the table is WITHOUT ROWID
+        let is_rowid_alias = is_primary && matches!(column_def.data_type, DataType::Integer(None));
+        if engine == Engine::Sqlite {
+            if is_autoincrement && !is_rowid_alias {
+                return Err(Diagnostic::at_span(
+                    format!(
+                        "AUTOINCREMENT is only allowed on an INTEGER PRIMARY KEY, {} is {}",
+                        column_def.name, column_def.data_type
+                    ),
+                    column_def.name.span,
+                )
+                .into());
+            }
+            if is_rowid_alias || (is_primary && without_rowid) {
+                is_nullable = false;
+            }
+        } else if is_primary {
+            is_nullable = false;
+        }
+
+        let is_serial = matches!(
+            &column_def.data_type,
+            DataType::Custom(name, _) if object_name_last(name).to_uppercase().contains("SERIAL")
+        );
+        if is_autoincrement
+            || is_serial
+            || (engine == Engine::Sqlite && is_rowid_alias && !without_rowid)
+        {
+            generated = generated.or(Some(Generated::AutoIncrement));
         }
+
+        let data_type = DType::for_column(
+            column_def.data_type.clone(),
+            is_nullable,
+            engine,
+            types,
+            enums,
+            table_name,
+            &column_def.name.value,
+        );
+        Ok(Column {
+            name: column_def.name.value.clone(),
+            default: default.map(|expr| ColumnDefault::new(expr, &data_type)),
+            data_type,
+            generated,
+        })
     }
 }
 
 pub fn process_sql_file(content: String) -> Result<EngineData> {
-    let parser = SqlFileParser::default();
+    process_sql_file_for_engine(content, Engine::default())
+}
+
+pub fn process_sql_file_for_engine(content: String, engine: Engine) -> Result<EngineData> {
+    process_sql_file_with_types(content, engine, TypeSettings::default())
+}
+
+pub fn process_sql_file_with_types(
+    content: String,
+    engine: Engine,
+    types: TypeSettings,
+) -> Result<EngineData> {
+    let parser = SqlFileParser::new(engine);
     let statements = parser.parse_sql(&content)?;
 
-    let mut engine_data = EngineData { tables: vec![] };
+    let mut engine_data = EngineData::new(engine);
+    engine_data.types = types;
     SchemaAnalyzer::analyze_sql_statements(&mut engine_data, statements)?;
 
     Ok(engine_data)
@@ -0,0 +1,176 @@
+use crate::analyzer::types::{Column, EngineData, Table};
+use crate::diagnostics::Diagnostic;
+use anyhow::Result;
+use sqlparser::ast::*;
+
+/// Tables visible to the expressions of a statement, addressed by their alias or name.
+/// Subqueries get a child scope that falls back to the enclosing one.
+pub struct Scope<'a> {
+    pub relations: Vec<ScopeRelation<'a>>,
+    /// Aliases of derived tables and table functions whose columns are unknown
+    pub opaque: Vec<String>,
+    parent: Option<&'a Scope<'a>>,
+}
+
+pub struct ScopeRelation<'a> {
+    /// Alias if one was given, otherwise the table name
+    pub name: String,
+    pub table: &'a Table,
+    /// Set when the relation is on the optional side of an outer join, in which case
+    /// every one of its columns can come back as NULL
+    pub nullable: bool,
+}
+
+impl<'a> Scope<'a> {
+    pub fn new(parent: Option<&'a Scope<'a>>) -> Self {
+        Self {
+            relations: vec![],
+            opaque: vec![],
+            parent,
+        }
+    }
+
+    pub fn add_table(
+        &mut self,
+        engine_data: &'a EngineData,
+        name: &ObjectName,
+        alias: Option<&TableAlias>,
+    ) -> Result<()> {
+        let table_name = object_name_last(name);
+        let table = engine_data.find_table(&table_name).ok_or_else(|| {
+            Diagnostic::at_span(format!("unknown table {}", table_name), name.span())
+        })?;
+
+        self.relations.push(ScopeRelation {
+            name: alias
+                .map(|alias| alias.name.value.clone())
+                .unwrap_or(table_name),
+            table,
+            nullable: false,
+        });
+
+        Ok(())
+    }
+
+    pub fn add_table_with_joins(
+        &mut self,
+        engine_data: &'a EngineData,
+        from: &TableWithJoins,
+    ) -> Result<()> {
+        let start = self.relations.len();
+        self.add_table_factor(engine_data, &from.relation)?;
+
+        for join in &from.joins {
+            let joined = self.relations.len();
+            self.add_table_factor(engine_data, &join.relation)?;
+
+            let (left_nullable, right_nullable) = match join.join_operator {
+                JoinOperator::Left(_) | JoinOperator::LeftOuter(_) | JoinOperator::OuterApply => {
+                    (false, true)
+                }
+                JoinOperator::Right(_) | JoinOperator::RightOuter(_) => (true, false),
+                JoinOperator::FullOuter(_) => (true, true),
+                _ => (false, false),
+            };
+
+            if left_nullable {
+                self.relations[start..joined]
+                    .iter_mut()
+                    .for_each(|relation| relation.nullable = true);
+            }
+            if right_nullable {
+                self.relations[joined..]
+                    .iter_mut()
+                    .for_each(|relation| relation.nullable = true);
+            }
+        }
+        Ok(())
+    }
+
+    fn add_table_factor(
+        &mut self,
+        engine_data: &'a EngineData,
+        factor: &TableFactor,
+    ) -> Result<()> {
+        match factor {
+            TableFactor::Table { name, alias, .. } => {
+                self.add_table(engine_data, name, alias.as_ref())
+            }
+            TableFactor::NestedJoin {
+                table_with_joins, ..
+            } => self.add_table_with_joins(engine_data, table_with_joins),
+            // Derived tables and table functions have no schema we could look up
+            TableFactor::Derived { alias, .. } | TableFactor::TableFunction { alias, .. } => {
+                self.opaque.push(
+                    alias
+                        .as_ref()
+                        .map(|alias| alias.name.value.clone())
+                        .unwrap_or_default(),
+                );
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Resolves a plain or qualified column reference to its schema column
+    pub fn resolve_column(&self, expr: &Expr) -> Option<&'a Column> {
+        self.lookup(expr).map(|(_, column)| column)
+    }
+
+    /// Resolves a plain or qualified column reference along with the relation it belongs to
+    pub fn lookup(&self, expr: &Expr) -> Option<(&ScopeRelation<'a>, &'a Column)> {
+        let found = match expr {
+            Expr::Identifier(ident) => self.relations.iter().find_map(|relation| {
+                relation
+                    .table
+                    .find_column(&ident.value)
+                    .map(|column| (relation, column))
+            }),
+            Expr::CompoundIdentifier(idents) if idents.len() >= 2 => {
+                let qualifier = &idents[idents.len() - 2].value;
+                let column = &idents[idents.len() - 1].value;
+                self.relation(qualifier).and_then(|relation| {
+                    relation
+                        .table
+                        .find_column(column)
+                        .map(|column| (relation, column))
+                })
+            }
+            _ => None,
+        };
+
+        found.or_else(|| self.parent.and_then(|parent| parent.lookup(expr)))
+    }
+
+    pub fn relation(&self, name: &str) -> Option<&ScopeRelation<'a>> {
+        self.relations
+            .iter()
+            .find(|relation| relation.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Whether an unresolved column could come from a relation without a known schema
+    pub fn is_opaque(&self, expr: &Expr) -> bool {
+        let local = match expr {
+            Expr::CompoundIdentifier(idents) if idents.len() >= 2 => {
+                let qualifier = &idents[idents.len() - 2].value;
+                self.opaque
+                    .iter()
+                    .any(|alias| alias.eq_ignore_ascii_case(qualifier))
+            }
+            _ => !self.opaque.is_empty(),
+        };
+
+        local || self.parent.is_some_and(|parent| parent.is_opaque(expr))
+    }
+}
+
+/// Returns the unqualified, unquoted name of a possibly schema-qualified object, the name
+/// tables, types and indexes are recorded under
+pub fn object_name_last(name: &ObjectName) -> String {
+    name.0
+        .last()
+        .and_then(|part| part.as_ident())
+        .map(|ident| ident.value.clone())
+        .unwrap_or_else(|| name.to_string())
+}
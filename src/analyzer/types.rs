@@ -1,25 +1,387 @@
-use crate::analyzer::PhpDataType;
+use crate::analyzer::{
+    PhpDataType, PhpType, map_sql_to_php_data_type, object_name_last, php_class_name, php_literal,
+};
+use crate::configuration::{Engine, TypeSettings};
 use serde::Serialize;
-use sqlparser::ast::DataType;
+use sqlparser::ast::{DataType, EnumMember, Expr, ReferentialAction};
 
+#[derive(Clone)]
 pub struct EngineData {
+    pub engine: Engine,
+    pub types: TypeSettings,
     pub tables: Vec<Table>,
+    /// Named enum types, such as Postgres' `CREATE TYPE mood AS ENUM (...)`
+    pub enums: Vec<EnumType>,
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct EnumType {
+    pub name: String,
+    pub values: Vec<String>,
+}
+
+#[derive(Default, Clone)]
 pub struct Table {
     pub name: String,
     pub columns: Vec<Column>,
+    /// Columns of the primary key in key order, empty when the table has none
+    pub primary_key: Vec<String>,
+    pub unique_constraints: Vec<UniqueConstraint>,
+    pub foreign_keys: Vec<ForeignKey>,
+    pub indexes: Vec<Index>,
+    pub checks: Vec<CheckConstraint>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UniqueConstraint {
+    pub name: Option<String>,
+    pub columns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ForeignKey {
+    pub name: Option<String>,
+    pub columns: Vec<String>,
+    pub referenced_table: String,
+    /// Referenced columns, in the order of `columns`
+    pub referenced_columns: Vec<String>,
+    pub on_delete: Option<ReferentialAction>,
+    pub on_update: Option<ReferentialAction>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Index {
+    pub name: Option<String>,
+    /// Indexed columns, or the SQL of an indexed expression
+    pub columns: Vec<String>,
+    pub unique: bool,
 }
 
-#[derive(Serialize)]
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckConstraint {
+    pub name: Option<String>,
+    /// The condition as SQL
+    pub expr: String,
+}
+
+#[derive(Clone, Serialize)]
 pub struct Column {
     pub name: String,
     pub data_type: DType,
+    /// Value the database fills in when an insert leaves the column out
+    pub default: Option<ColumnDefault>,
+    /// Set when the database generates the value itself
+    pub generated: Option<Generated>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnDefault {
+    pub expr: Expr,
+    /// The default as a PHP literal, only set for constants of the column's type
+    pub php_value: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum Generated {
+    /// `AUTO_INCREMENT`, `SERIAL` or a SQLite rowid alias
+    AutoIncrement,
+    /// `GENERATED ... AS IDENTITY`
+    Identity,
+    /// `GENERATED ALWAYS AS (expr)`, which cannot be written at all
+    Computed(String),
 }
 
-#[derive(Serialize)]
+#[derive(Clone, Serialize)]
 pub struct DType {
     pub sql_type: DataType,
     pub php_type: PhpDataType,
     pub nullable: bool,
 }
+
+impl DType {
+    pub fn new(sql_type: DataType, nullable: bool, engine: Engine, types: TypeSettings) -> Self {
+        Self {
+            php_type: map_sql_to_php_data_type(&sql_type, nullable, engine, types),
+            sql_type,
+            nullable,
+        }
+    }
+
+    /// Type of a column, where MySQL's inline `ENUM(...)` and `SET(...)` become an enum
+    /// named after the table and column and a named enum type one named after the type
+    pub fn for_column(
+        sql_type: DataType,
+        nullable: bool,
+        engine: Engine,
+        types: TypeSettings,
+        enums: &[EnumType],
+        table: &str,
+        column: &str,
+    ) -> Self {
+        let enum_name = match &sql_type {
+            DataType::Enum(..) | DataType::Set(..) => Some(format!("{}_{}", table, column)),
+            DataType::Custom(name, _) => {
+                let name = object_name_last(name);
+                enums
+                    .iter()
+                    .find(|enum_type| enum_type.name.eq_ignore_ascii_case(&name))
+                    .map(|enum_type| enum_type.name.clone())
+            }
+            _ => None,
+        };
+
+        match enum_name {
+            Some(name) => {
+                let class = php_class_name(&name);
+                let php_type = match sql_type {
+                    DataType::Set(_) => PhpType::EnumSet(class),
+                    _ => PhpType::Enum(class),
+                };
+                Self {
+                    php_type: PhpDataType::new(php_type, nullable),
+                    sql_type,
+                    nullable,
+                }
+            }
+            None => Self::new(sql_type, nullable, engine, types),
+        }
+    }
+
+    /// Values the column accepts, if it is an enum
+    pub fn enum_values(&self, enums: &[EnumType]) -> Option<Vec<String>> {
+        match &self.sql_type {
+            DataType::Enum(members, _) => Some(
+                members
+                    .iter()
+                    .map(|member| match member {
+                        EnumMember::Name(name) | EnumMember::NamedValue(name, _) => name.clone(),
+                    })
+                    .collect(),
+            ),
+            DataType::Set(values) => Some(values.clone()),
+            DataType::Custom(name, _) => {
+                let name = object_name_last(name);
+                enums
+                    .iter()
+                    .find(|enum_type| enum_type.name.eq_ignore_ascii_case(&name))
+                    .map(|enum_type| enum_type.values.clone())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl ColumnDefault {
+    pub fn new(expr: Expr, data_type: &DType) -> Self {
+        Self {
+            php_value: php_literal(&expr, &data_type.php_type.php_type),
+            expr,
+        }
+    }
+}
+
+impl Column {
+    /// Whether an insert can leave the column out
+    pub fn has_default(&self) -> bool {
+        self.default.is_some() || self.generated.is_some()
+    }
+
+    /// Changes the type of the column, converting its default along with it
+    pub fn retype(&mut self, data_type: DType) {
+        self.data_type = data_type;
+        if let Some(default) = self.default.take() {
+            self.default = Some(ColumnDefault::new(default.expr, &self.data_type));
+        }
+    }
+
+    pub fn set_nullable(&mut self, nullable: bool) {
+        self.data_type.nullable = nullable;
+        self.data_type.php_type =
+            PhpDataType::new(self.data_type.php_type.php_type.clone(), nullable);
+    }
+}
+
+impl EngineData {
+    pub fn new(engine: Engine) -> Self {
+        Self {
+            engine,
+            types: TypeSettings::default(),
+            tables: vec![],
+            enums: vec![],
+        }
+    }
+
+    pub fn table_index(&self, name: &str) -> Option<usize> {
+        self.tables
+            .iter()
+            .position(|table| table.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn enum_index(&self, name: &str) -> Option<usize> {
+        self.enums
+            .iter()
+            .position(|enum_type| enum_type.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn find_table(&self, name: &str) -> Option<&Table> {
+        self.tables
+            .iter()
+            .find(|table| table.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Points the foreign keys of every table at a renamed table
+    pub fn rename_table_references(&mut self, old: &str, new: &str) {
+        for foreign_key in self.tables.iter_mut().flat_map(|t| &mut t.foreign_keys) {
+            if foreign_key.referenced_table.eq_ignore_ascii_case(old) {
+                foreign_key.referenced_table = new.to_string();
+            }
+        }
+    }
+
+    /// Follows a column rename in the table at `table_index` and in the foreign keys of every
+    /// table that references it
+    pub fn rename_column_references(&mut self, table_index: usize, old: &str, new: &str) {
+        self.tables[table_index].rename_column_references(old, new);
+
+        let table_name = self.tables[table_index].name.clone();
+        for foreign_key in self.tables.iter_mut().flat_map(|t| &mut t.foreign_keys) {
+            if !foreign_key
+                .referenced_table
+                .eq_ignore_ascii_case(&table_name)
+            {
+                continue;
+            }
+            for column in &mut foreign_key.referenced_columns {
+                if column.eq_ignore_ascii_case(old) {
+                    *column = new.to_string();
+                }
+            }
+        }
+    }
+
+    /// Drops the foreign keys that reference a dropped table
+    pub fn drop_table_references(&mut self, name: &str) {
+        for table in &mut self.tables {
+            table
+                .foreign_keys
+                .retain(|foreign_key| !foreign_key.referenced_table.eq_ignore_ascii_case(name));
+        }
+    }
+}
+
+impl Table {
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.columns
+            .iter()
+            .position(|column| column.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn find_column(&self, name: &str) -> Option<&Column> {
+        self.columns
+            .iter()
+            .find(|column| column.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Follows a column rename in the keys, constraints and indexes of the table
+    pub fn rename_column_references(&mut self, old: &str, new: &str) {
+        let rename = |columns: &mut Vec<String>| {
+            for column in columns.iter_mut() {
+                if column.eq_ignore_ascii_case(old) {
+                    *column = new.to_string();
+                }
+            }
+        };
+
+        rename(&mut self.primary_key);
+        self.unique_constraints
+            .iter_mut()
+            .for_each(|unique| rename(&mut unique.columns));
+        self.foreign_keys
+            .iter_mut()
+            .for_each(|foreign_key| rename(&mut foreign_key.columns));
+        self.indexes
+            .iter_mut()
+            .for_each(|index| rename(&mut index.columns));
+    }
+
+    /// Drops the keys, constraints and indexes a dropped column was part of
+    pub fn drop_column_references(&mut self, name: &str) {
+        let uses = |columns: &[String]| columns.iter().any(|c| c.eq_ignore_ascii_case(name));
+
+        if uses(&self.primary_key) {
+            self.primary_key.clear();
+        }
+        self.unique_constraints
+            .retain(|unique| !uses(&unique.columns));
+        self.foreign_keys
+            .retain(|foreign_key| !uses(&foreign_key.columns));
+        self.indexes.retain(|index| !uses(&index.columns));
+    }
+
+    /// Removes the unique, foreign key or check constraint called `name`
+    pub fn drop_constraint(&mut self, name: &str) -> bool {
+        let named = |constraint: &Option<String>| {
+            constraint
+                .as_deref()
+                .is_some_and(|constraint| constraint.eq_ignore_ascii_case(name))
+        };
+
+        let count = self.unique_constraints.len() + self.foreign_keys.len() + self.checks.len();
+        self.unique_constraints
+            .retain(|unique| !named(&unique.name));
+        self.foreign_keys
+            .retain(|foreign_key| !named(&foreign_key.name));
+        self.checks.retain(|check| !named(&check.name));
+        count != self.unique_constraints.len() + self.foreign_keys.len() + self.checks.len()
+    }
+
+    /// Whether the table has an index or unique constraint called `name`
+    pub fn has_index(&self, name: &str) -> bool {
+        self.indexes
+            .iter()
+            .map(|index| &index.name)
+            .chain(self.unique_constraints.iter().map(|unique| &unique.name))
+            .any(|index| {
+                index
+                    .as_deref()
+                    .is_some_and(|index| index.eq_ignore_ascii_case(name))
+            })
+    }
+
+    /// Removes the index or unique constraint called `name`
+    pub fn drop_index(&mut self, name: &str) -> bool {
+        let named = |index: &Option<String>| {
+            index
+                .as_deref()
+                .is_some_and(|index| index.eq_ignore_ascii_case(name))
+        };
+
+        let count = self.indexes.len() + self.unique_constraints.len();
+        self.indexes.retain(|index| !named(&index.name));
+        self.unique_constraints
+            .retain(|unique| !named(&unique.name));
+        count != self.indexes.len() + self.unique_constraints.len()
+    }
+
+    /// Renames the unique, foreign key or check constraint called `old`
+    pub fn rename_constraint(&mut self, old: &str, new: &str) {
+        let names = self
+            .unique_constraints
+            .iter_mut()
+            .map(|unique| &mut unique.name)
+            .chain(
+                self.foreign_keys
+                    .iter_mut()
+                    .map(|foreign_key| &mut foreign_key.name),
+            )
+            .chain(self.checks.iter_mut().map(|check| &mut check.name));
+        for name in names {
+            if name
+                .as_deref()
+                .is_some_and(|name| name.eq_ignore_ascii_case(old))
+            {
+                *name = Some(new.to_string());
+            }
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use colored::*;
+use sqlparser::parser::ParserError;
+use sqlparser::tokenizer::Span;
+use std::fmt;
+
+/// A problem found in a schema or query file, pointing at the offending source
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub message: String,
+    pub path: Option<String>,
+    /// Line and column start at 1, a line of 0 means the location is unknown
+    pub line: usize,
+    pub column: usize,
+    /// Number of characters to underline
+    pub width: usize,
+    /// Source line the diagnostic points at
+    pub snippet: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            path: None,
+            line: 0,
+            column: 0,
+            width: 1,
+            snippet: None,
+        }
+    }
+
+    pub fn at(message: impl Into<String>, line: usize, column: usize, width: usize) -> Self {
+        Self {
+            line,
+            column,
+            width: width.max(1),
+            ..Self::new(message)
+        }
+    }
+
+    /// Points at a span of the parsed AST, an empty span leaves the location unknown
+    pub fn at_span(message: impl Into<String>, span: Span) -> Self {
+        let width = if span.start.line == span.end.line {
+            span.end.column.saturating_sub(span.start.column) as usize
+        } else {
+            1
+        };
+        Self::at(
+            message,
+            span.start.line as usize,
+            span.start.column as usize,
+            width,
+        )
+    }
+
+    /// Converts a sqlparser error, taking the location out of its message
+    pub fn from_parser_error(error: &ParserError) -> Self {
+        let message = match error {
+            ParserError::TokenizerError(message) | ParserError::ParserError(message) => {
+                message.clone()
+            }
+            ParserError::RecursionLimitExceeded => "recursion limit exceeded".to_string(),
+        };
+
+        let located = message
+            .rsplit_once(" at Line: ")
+            .and_then(|(text, location)| {
+                let (line, column) = location.split_once(", Column: ")?;
+                Some((text.to_string(), line.parse().ok()?, column.parse().ok()?))
+            });
+
+        match located {
+            Some((text, line, column)) => Self::at(text, line, column, 1),
+            None => Self::new(message),
+        }
+    }
+
+    /// Prefixes the message, e.g. with the name of the query it belongs to
+    pub fn context(mut self, context: impl fmt::Display) -> Self {
+        self.message = format!("{}: {}", context, self.message);
+        self
+    }
+
+    /// Moves the diagnostic down by `lines`, for sources parsed from a fragment of a file
+    pub fn shift(mut self, lines: usize) -> Self {
+        if self.line > 0 {
+            self.line += lines;
+        }
+        self
+    }
+
+    /// Places the diagnostic in a file, taking the snippet from its content
+    pub fn in_source(mut self, path: &str, content: &str) -> Self {
+        if self.path.is_none() {
+            self.path = Some(path.to_string());
+        }
+        if self.snippet.is_none() && self.line > 0 {
+            self.snippet = content.lines().nth(self.line - 1).map(str::to_string);
+        }
+        self
+    }
+
+    pub fn render(&self, color: bool) -> String {
+        let paint = |text: &str, style: fn(ColoredString) -> ColoredString| {
+            if color {
+                style(text.normal()).to_string()
+            } else {
+                text.to_string()
+            }
+        };
+
+        let mut output = format!(
+            "{}{}",
+            paint("error", |s| s.red().bold()),
+            paint(&format!(": {}", self.message), |s| s.bold())
+        );
+
+        let location = match (&self.path, self.line) {
+            (Some(path), 0) => Some(path.clone()),
+            (Some(path), line) => Some(format!("{}:{}:{}", path, line, self.column)),
+            (None, 0) => None,
+            (None, line) => Some(format!("{}:{}", line, self.column)),
+        };
+        let Some(location) = location else {
+            return output;
+        };
+
+        let gutter = " ".repeat(self.line.to_string().len());
+        let bar = paint("|", |s| s.blue().bold());
+        output.push_str(&format!(
+            "\n{}{} {}",
+            gutter,
+            paint("-->", |s| s.blue().bold()),
+            location
+        ));
+
+        if let Some(snippet) = &self.snippet {
+            let indent = " ".repeat(self.column.saturating_sub(1));
+            let carets = "^".repeat(self.width);
+            output.push_str(&format!("\n{} {}", gutter, bar));
+            output.push_str(&format!(
+                "\n{} {} {}",
+                paint(&self.line.to_string(), |s| s.blue().bold()),
+                bar,
+                snippet
+            ));
+            output.push_str(&format!(
+                "\n{} {} {}{}",
+                gutter,
+                bar,
+                indent,
+                paint(&carets, |s| s.red().bold())
+            ));
+        }
+
+        output
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render(false))
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+/// Every diagnostic collected while processing a set of files
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    pub items: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.items.push(diagnostic);
+    }
+
+    /// Adds an error, unpacking diagnostics carried inside it
+    pub fn push_error(&mut self, error: anyhow::Error) {
+        let error = match error.downcast::<Diagnostics>() {
+            Ok(diagnostics) => return self.items.extend(diagnostics.items),
+            Err(error) => error,
+        };
+        match error.downcast::<Diagnostic>() {
+            Ok(diagnostic) => self.items.push(diagnostic),
+            Err(error) => self.items.push(Diagnostic::new(error.to_string())),
+        }
+    }
+
+    /// Applies `f` to every diagnostic, e.g. to place them in a file
+    pub fn map(self, f: impl Fn(Diagnostic) -> Diagnostic) -> Self {
+        Self {
+            items: self.items.into_iter().map(f).collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn into_result(self) -> anyhow::Result<()> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self.into())
+        }
+    }
+
+    pub fn render(&self, color: bool) -> String {
+        let mut output = self
+            .items
+            .iter()
+            .map(|diagnostic| diagnostic.render(color))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        if self.items.len() > 1 {
+            output.push_str(&format!("\n\n{} errors found", self.items.len()));
+        }
+        output
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render(false))
+    }
+}
+
+impl std::error::Error for Diagnostics {}
+
+/// Turns any error into diagnostics so errors from several sources can be merged
+pub fn into_diagnostics(error: anyhow::Error) -> Diagnostics {
+    let mut diagnostics = Diagnostics::default();
+    diagnostics.push_error(error);
+    diagnostics
+}
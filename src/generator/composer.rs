@@ -0,0 +1,83 @@
+use anyhow::{Result, anyhow};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Component, Path};
+
+/// Maps `namespace` to `output` in the `autoload.psr-4` section of `composer`, creating the
+/// file when there is none. Returns whether the file changed.
+pub fn register_autoload(composer: &Path, namespace: Option<&str>, output: &Path) -> Result<bool> {
+    let mut document = match fs::read_to_string(composer) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| anyhow!("Failed to parse {}: {}", composer.display(), e))?,
+        Err(_) if !composer.exists() => Value::Object(Map::new()),
+        Err(e) => return Err(anyhow!("Error reading file {}: {}", composer.display(), e)),
+    };
+
+    let prefix = match namespace.filter(|namespace| !namespace.is_empty()) {
+        Some(namespace) => format!("{}\\", namespace.trim_matches('\\')),
+        None => String::new(),
+    };
+    let directory = Value::String(autoload_directory(composer, output)?);
+
+    let psr4 = object_entry(&mut document, "autoload")
+        .and_then(|autoload| object_entry(autoload, "psr-4"))
+        .and_then(|psr4| psr4.as_object_mut())
+        .ok_or_else(|| anyhow!("autoload.psr-4 of {} is not an object", composer.display()))?;
+    if psr4.get(&prefix) == Some(&directory) {
+        return Ok(false);
+    }
+    psr4.insert(prefix, directory);
+
+    // Composer writes its files with four spaces and a trailing newline
+    let mut content = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b"    ");
+    let mut serializer = serde_json::Serializer::with_formatter(&mut content, formatter);
+    serde::Serialize::serialize(&document, &mut serializer)?;
+    content.push(b'\n');
+
+    fs::write(composer, content)
+        .map_err(|e| anyhow!("Failed to write {}: {}", composer.display(), e))?;
+    Ok(true)
+}
+
+/// The member `key` of an object, added as an empty object when missing
+fn object_entry<'a>(value: &'a mut Value, key: &str) -> Option<&'a mut Value> {
+    Some(
+        value
+            .as_object_mut()?
+            .entry(key)
+            .or_insert_with(|| Value::Object(Map::new())),
+    )
+}
+
+/// `output` relative to the directory of `composer`, in the `src/Db/` form Composer uses
+fn autoload_directory(composer: &Path, output: &Path) -> Result<String> {
+    let base = components(composer.parent().unwrap_or(Path::new("")));
+    let output_parts = components(output);
+
+    let relative = output_parts
+        .strip_prefix(base.as_slice())
+        .filter(|relative| {
+            relative
+                .iter()
+                .all(|component| matches!(component, Component::Normal(_)))
+        })
+        .ok_or_else(|| {
+            anyhow!(
+                "Output directory {} is not inside the directory of {}",
+                output.display(),
+                composer.display()
+            )
+        })?;
+
+    Ok(relative
+        .iter()
+        .map(|component| format!("{}/", component.as_os_str().to_string_lossy()))
+        .collect())
+}
+
+fn components(path: &Path) -> Vec<Component<'_>> {
+    path.components()
+        .filter(|component| *component != Component::CurDir)
+        .collect()
+}
@@ -1,29 +1,102 @@
-use crate::analyzer::EngineData;
-use crate::configuration::SqlSettings;
+use crate::analyzer::{
+    AnalyzedQuery, EngineData, PhpDataType, PhpType, QueryParam, ResultColumn,
+    is_reserved_class_name, php_class_name, php_enum_case,
+};
+use crate::configuration::{EntityStyle, OutputLayout, SqlSettings};
+use crate::generator::PhpFeatures;
+use crate::generator::composer::register_autoload;
+use crate::generator::imports::Imports;
+use crate::parser::QueryCommand;
 use anyhow::{Result, anyhow};
+use serde::Serialize;
+use sqlparser::ast::DataType;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tera::{Context, Tera};
 
 pub struct TemplateEngine {
     pub engine_data: EngineData,
+    pub queries: Vec<AnalyzedQuery>,
     tera: Tera,
     output_dir: String,
     namespace: Option<String>,
+    entity_style: EntityStyle,
+    php: PhpFeatures,
+    layout: OutputLayout,
+    composer: Option<String>,
+}
+
+/// The kinds of generated classes, which the PSR-4 layout puts in sub-namespaces of their own
+#[derive(Clone, Copy, PartialEq)]
+enum ClassKind {
+    Entity,
+    Row,
+    Enum,
+    Queries,
+}
+
+impl ClassKind {
+    fn sub_namespace(self) -> &'static str {
+        match self {
+            ClassKind::Entity => "Entity",
+            ClassKind::Row => "Row",
+            ClassKind::Enum => "Enum",
+            ClassKind::Queries => "Queries",
+        }
+    }
 }
 
 impl TemplateEngine {
-    pub fn new(engine_data: EngineData, config: &SqlSettings) -> Result<Self> {
+    pub fn new(
+        engine_data: EngineData,
+        queries: Vec<AnalyzedQuery>,
+        config: &SqlSettings,
+    ) -> Result<Self> {
+        let php = PhpFeatures::for_version(config.php_version);
+        if config.entity_style == EntityStyle::Readonly && !php.readonly_properties {
+            return Err(anyhow!(
+                "The readonly entity style needs PHP 8.1 or later, php_version is {}",
+                config.php_version
+            ));
+        }
+
         // Initialize Tera with templates
         let mut tera = Tera::default();
 
         // Load templates
+        tera.add_raw_template("macros.php", include_str!("../../templates/macros.php"))
+            .map_err(|e| anyhow!("Failed to load macros template: {}", e))?;
+
         tera.add_raw_template(
             "entity_class.php",
             include_str!("../../templates/entity_class.php"),
         )
         .map_err(|e| anyhow!("Failed to load entity template: {}", e))?;
 
+        tera.add_raw_template(
+            "readonly_entity_class.php",
+            include_str!("../../templates/readonly_entity_class.php"),
+        )
+        .map_err(|e| anyhow!("Failed to load readonly entity template: {}", e))?;
+
+        tera.add_raw_template(
+            "enum_class.php",
+            include_str!("../../templates/enum_class.php"),
+        )
+        .map_err(|e| anyhow!("Failed to load enum template: {}", e))?;
+
+        tera.add_raw_template(
+            "query_function.php",
+            include_str!("../../templates/query_function.php"),
+        )
+        .map_err(|e| anyhow!("Failed to load query function template: {}", e))?;
+
+        tera.add_raw_template(
+            "queries_class.php",
+            include_str!("../../templates/queries_class.php"),
+        )
+        .map_err(|e| anyhow!("Failed to load queries template: {}", e))?;
+
         // Create output directory if it doesn't exist
         let output_path = Path::new(config.output.as_str());
         if !output_path.exists() {
@@ -33,36 +106,195 @@ impl TemplateEngine {
 
         Ok(Self {
             engine_data,
+            queries,
             tera,
             namespace: config.namespace.clone(),
+            entity_style: config.entity_style,
+            php,
+            layout: config.layout,
+            composer: config.composer.clone(),
             output_dir: config.output.to_string(),
         })
     }
 
     pub fn generate_templates(&self) -> Result<()> {
+        self.check_class_names()?;
+        self.generate_enum_classes()?;
         self.generate_entity_classes()?;
-        // In the future, add more generation methods:
-        // self.generate_queries_class()?;
+        self.generate_row_classes()?;
+        self.generate_queries_class()?;
+
+        if let Some(composer) = &self.composer {
+            let composer = Path::new(composer);
+            if register_autoload(
+                composer,
+                self.namespace.as_deref(),
+                Path::new(&self.output_dir),
+            )? {
+                println!("Registered the autoload root in {}", composer.display());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Generates a string backed enum for every enum type and MySQL `ENUM(...)` or `SET(...)`
+    /// column
+    fn generate_enum_classes(&self) -> Result<()> {
+        for EnumClass {
+            class_name, values, ..
+        } in self.enum_classes()
+        {
+            let cases = values
+                .iter()
+                .map(|value| EnumCase {
+                    name: php_enum_case(value),
+                    value: value.replace('\\', "\\\\").replace('\'', "\\'"),
+                })
+                .collect::<Vec<_>>();
+
+            let mut context = Context::new();
+            context.insert("class_name", &class_name);
+            context.insert("namespace", &self.namespace(ClassKind::Enum));
+            context.insert("php", &self.php);
+            context.insert("uses", &Vec::<String>::new());
+            context.insert("cases", &cases);
+
+            let rendered = self.tera.render("enum_class.php", &context).map_err(|e| {
+                anyhow!("Failed to render enum template for {}: {:?}", class_name, e)
+            })?;
+
+            let file_path = self.class_path(ClassKind::Enum, &class_name)?;
+            fs::write(&file_path, rendered)
+                .map_err(|e| anyhow!("Failed to write enum file {}: {}", file_path.display(), e))?;
+
+            println!("Generated enum {} at {}", class_name, file_path.display());
+        }
 
         Ok(())
     }
 
+    /// Enum classes with their values, named enum types first. Columns of a named type share
+    /// its class, every MySQL `ENUM(...)` or `SET(...)` column has one of its own.
+    fn enum_classes(&self) -> Vec<EnumClass> {
+        let mut classes: Vec<EnumClass> = self
+            .engine_data
+            .enums
+            .iter()
+            .map(|enum_type| EnumClass {
+                class_name: php_class_name(&enum_type.name),
+                values: enum_type.values.clone(),
+                source: format!("type {}", enum_type.name),
+            })
+            .collect();
+
+        for table in &self.engine_data.tables {
+            for column in &table.columns {
+                let (PhpType::Enum(class_name) | PhpType::EnumSet(class_name)) =
+                    &column.data_type.php_type.php_type
+                else {
+                    continue;
+                };
+                if !matches!(
+                    column.data_type.sql_type,
+                    DataType::Enum(..) | DataType::Set(..)
+                ) {
+                    continue;
+                }
+                if let Some(values) = column.data_type.enum_values(&self.engine_data.enums) {
+                    classes.push(EnumClass {
+                        class_name: class_name.clone(),
+                        values,
+                        source: format!("column {}.{}", table.name, column.name),
+                    });
+                }
+            }
+        }
+
+        classes
+    }
+
+    /// Rejects class names PHP reserves, classes that would end up with the same name in the
+    /// same namespace and enum values that would end up with the same case name
+    fn check_class_names(&self) -> Result<()> {
+        let classes = self.generated_classes();
+        let mut problems = vec![];
+        for (index, (kind, class_name, source)) in classes.iter().enumerate() {
+            if is_reserved_class_name(class_name) {
+                problems.push(format!(
+                    "{} would generate class {}, which is a reserved word in PHP",
+                    source, class_name
+                ));
+            }
+            let clash = classes[..index].iter().find(|(other_kind, other, _)| {
+                other.eq_ignore_ascii_case(class_name)
+                    && (self.layout == OutputLayout::Flat || other_kind == kind)
+            });
+            if let Some((_, _, other_source)) = clash {
+                problems.push(format!(
+                    "{} and {} would both generate class {}",
+                    other_source, source, class_name
+                ));
+            }
+        }
+
+        for class in &self.enum_classes() {
+            let cases: Vec<String> = class.values.iter().map(|v| php_enum_case(v)).collect();
+            for (index, case) in cases.iter().enumerate() {
+                // `class` is the one name a class constant, and so an enum case, cannot have
+                if case.eq_ignore_ascii_case("class") {
+                    problems.push(format!(
+                        "value '{}' of {} would generate case {}, which PHP reserves",
+                        class.values[index], class.source, case
+                    ));
+                }
+                if let Some(other) = cases[..index].iter().position(|other| other == case) {
+                    problems.push(format!(
+                        "values '{}' and '{}' of {} would both generate case {}",
+                        class.values[other], class.values[index], class.source, case
+                    ));
+                }
+            }
+        }
+
+        if problems.is_empty() {
+            return Ok(());
+        }
+        Err(anyhow!(
+            "Cannot generate PHP classes, rename the tables, types or values involved:\n{}",
+            problems.join("\n")
+        ))
+    }
+
     fn generate_entity_classes(&self) -> Result<()> {
+        let namespace = self.namespace(ClassKind::Entity);
+        let local_classes = self.local_classes(ClassKind::Entity);
         for table in &self.engine_data.tables {
             let mut context = Context::new();
 
             // Convert table name to PascalCase for class name
-            let class_name = self.to_pascal_case(&table.name);
+            let class_name = php_class_name(&table.name);
             context.insert("class_name", &class_name);
-            context.insert("namespace", &self.namespace);
+            context.insert("namespace", &namespace);
+            context.insert("php", &self.php);
 
             // Add columns to context
-            context.insert("columns", &table.columns);
+            let mut imports = Imports::new(namespace.as_deref(), &local_classes);
+            let columns = table
+                .columns
+                .iter()
+                .map(|column| {
+                    let column = ResultColumn::from_column(column, Some(&table.name));
+                    self.column_context(&column, &mut imports)
+                })
+                .collect::<Vec<_>>();
+            Self::insert_columns(&mut context, columns, true);
+            context.insert("uses", &imports.uses());
 
             // Render the template
             let rendered = self
                 .tera
-                .render("entity_class.php", &context)
+                .render(self.entity_template(), &context)
                 .map_err(|e| {
                     anyhow!(
                         "Failed to render entity template for {}: {:?}",
@@ -72,7 +304,7 @@ impl TemplateEngine {
                 })?;
 
             // Write the rendered template to a file
-            let file_path = Path::new(&self.output_dir).join(format!("{}.php", class_name));
+            let file_path = self.class_path(ClassKind::Entity, &class_name)?;
             fs::write(&file_path, rendered).map_err(|e| {
                 anyhow!("Failed to write entity file {}: {}", file_path.display(), e)
             })?;
@@ -87,22 +319,447 @@ impl TemplateEngine {
         Ok(())
     }
 
-    // Helper to convert snake_case to PascalCase
-    fn to_pascal_case(&self, s: &str) -> String {
-        let mut result = String::new();
-        let mut capitalize = true;
+    /// Generates a row class for every query whose result is not a table entity
+    fn generate_row_classes(&self) -> Result<()> {
+        let namespace = self.namespace(ClassKind::Row);
+        let local_classes = self.local_classes(ClassKind::Row);
+        for query in &self.queries {
+            if query.result_table.is_some() || query.result_columns.is_empty() {
+                continue;
+            }
+
+            let mut context = Context::new();
 
-        for c in s.chars() {
-            if c == '_' {
-                capitalize = true;
-            } else if capitalize {
-                result.push(c.to_ascii_uppercase());
-                capitalize = false;
-            } else {
-                result.push(c);
+            let class_name = self.row_class_name(query);
+            context.insert("class_name", &class_name);
+            context.insert("namespace", &namespace);
+            context.insert("php", &self.php);
+
+            let mut imports = Imports::new(namespace.as_deref(), &local_classes);
+            let columns = query
+                .result_columns
+                .iter()
+                .map(|column| self.column_context(column, &mut imports))
+                .collect::<Vec<_>>();
+            Self::insert_columns(&mut context, columns, false);
+            context.insert("uses", &imports.uses());
+
+            let rendered = self
+                .tera
+                .render(self.entity_template(), &context)
+                .map_err(|e| {
+                    anyhow!("Failed to render row template for {}: {:?}", query.name, e)
+                })?;
+
+            let file_path = self.class_path(ClassKind::Row, &class_name)?;
+            fs::write(&file_path, rendered)
+                .map_err(|e| anyhow!("Failed to write row file {}: {}", file_path.display(), e))?;
+
+            println!(
+                "Generated row class for query {} at {}",
+                query.name,
+                file_path.display()
+            );
+        }
+
+        Ok(())
+    }
+
+    fn generate_queries_class(&self) -> Result<()> {
+        if self.queries.is_empty() {
+            return Ok(());
+        }
+
+        let class_name = "Queries";
+        let namespace = self.namespace(ClassKind::Queries);
+        let local_classes = self.local_classes(ClassKind::Queries);
+        let mut imports = Imports::new(namespace.as_deref(), &local_classes);
+        let pdo = imports.name("\\PDO");
+        let queries = self
+            .queries
+            .iter()
+            .map(|query| self.query_context(query, &mut imports))
+            .collect::<Vec<_>>();
+
+        let mut context = Context::new();
+        context.insert("class_name", class_name);
+        context.insert("namespace", &namespace);
+        context.insert("php", &self.php);
+        context.insert("pdo", &pdo);
+        context.insert("queries", &queries);
+        context.insert("uses", &imports.uses());
+
+        let rendered = self
+            .tera
+            .render("queries_class.php", &context)
+            .map_err(|e| anyhow!("Failed to render queries template: {:?}", e))?;
+
+        let file_path = self.class_path(ClassKind::Queries, class_name)?;
+        fs::write(&file_path, rendered).map_err(|e| {
+            anyhow!(
+                "Failed to write queries file {}: {}",
+                file_path.display(),
+                e
+            )
+        })?;
+
+        println!(
+            "Generated {} query methods at {}",
+            queries.len(),
+            file_path.display()
+        );
+
+        Ok(())
+    }
+
+    fn query_context<'a>(
+        &self,
+        query: &'a AnalyzedQuery,
+        imports: &mut Imports,
+    ) -> QueryContext<'a> {
+        let entity_class = match &query.result_table {
+            Some(table) => Some(self.class_reference(ClassKind::Entity, &php_class_name(table))),
+            None if !query.result_columns.is_empty() => {
+                Some(self.class_reference(ClassKind::Row, &self.row_class_name(query)))
             }
+            None => None,
+        }
+        .map(|class| imports.name(&class));
+
+        let (return_type, return_type_hint) = match (query.command, &entity_class) {
+            (QueryCommand::One, Some(class)) => (format!("{}|null", class), format!("?{}", class)),
+            (QueryCommand::Many, Some(class)) => (format!("{}[]", class), "array".to_string()),
+            (QueryCommand::BatchOne, Some(class)) => {
+                (format!("list<{}|null>", class), "array".to_string())
+            }
+            (QueryCommand::ExecRows | QueryCommand::ExecLastId, _) => {
+                ("int".to_string(), "int".to_string())
+            }
+            (QueryCommand::ExecResult, _) => {
+                let statement = imports.name("\\PDOStatement");
+                (statement.clone(), statement)
+            }
+            _ => ("void".to_string(), "void".to_string()),
+        };
+
+        let params = query
+            .params
+            .iter()
+            .map(|param| QueryParam {
+                php_type: self.php_type(&param.php_type, imports),
+                default_value: self.default_value(&param.default_value, &param.php_type, imports),
+                ..param.clone()
+            })
+            .collect::<Vec<_>>();
+
+        let batch_shape = format!(
+            "array{{{}}}",
+            params
+                .iter()
+                .map(|param| format!("{}: {}", param.name, param.php_type.docblock_type))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+
+        let argument = |binding: &String, variable: String| match params
+            .iter()
+            .find(|param| &param.name == binding)
+        {
+            Some(param) => {
+                let pdo_type = pdo_type(&param.php_type);
+                // PDO hands a boolean to each driver in the form it takes
+                let value = if pdo_type == "PARAM_BOOL" {
+                    variable
+                } else {
+                    database_value(&param.php_type, &variable, self.php)
+                };
+                Argument { value, pdo_type }
+            }
+            None => Argument {
+                value: variable,
+                pdo_type: "PARAM_STR",
+            },
+        };
+        let arguments = query
+            .bindings
+            .iter()
+            .map(|binding| argument(binding, format!("${}", binding)))
+            .collect();
+        let batch_arguments = query
+            .bindings
+            .iter()
+            .map(|binding| argument(binding, format!("$params['{}']", binding)))
+            .collect();
+
+        let mut method_name = query.name.clone();
+        if let Some(first) = method_name.get_mut(0..1) {
+            first.make_ascii_lowercase();
+        }
+
+        QueryContext {
+            name: &query.name,
+            method_name,
+            command: query.command,
+            is_batch: query.command.is_batch(),
+            batch_shape,
+            sql: query.sql.replace('\\', "\\\\").replace('\'', "\\'"),
+            params,
+            arguments,
+            batch_arguments,
+            entity_class,
+            return_type,
+            return_type_hint,
+        }
+    }
+
+    fn entity_template(&self) -> &'static str {
+        match self.entity_style {
+            EntityStyle::Mutable => "entity_class.php",
+            EntityStyle::Readonly => "readonly_entity_class.php",
+        }
+    }
+
+    /// Adds the properties of an entity or row class, and the same as constructor parameters
+    /// with the required ones first. Entities start out with their constant defaults.
+    fn insert_columns(context: &mut Context, columns: Vec<ColumnContext>, with_defaults: bool) {
+        let mut parameters = columns.iter().collect::<Vec<_>>();
+        parameters.sort_by_key(|column| {
+            column.column.php_type.is_nullable
+                || (with_defaults && column.column.default_value.is_some())
+        });
+
+        context.insert("parameters", &parameters);
+        context.insert("columns", &columns);
+        context.insert("with_defaults", &with_defaults);
+    }
+
+    /// A property of an entity or row class, with the classes of its type imported
+    fn column_context(&self, column: &ResultColumn, imports: &mut Imports) -> ColumnContext {
+        let php_type = self.php_type(&column.php_type, imports);
+        let default_value = self.default_value(&column.default_value, &column.php_type, imports);
+        ColumnContext {
+            wither: format!("with{}", php_class_name(&column.name)),
+            database_value: database_value(&php_type, &format!("$this->{}", column.name), self.php),
+            column: ResultColumn {
+                php_type,
+                default_value,
+                ..column.clone()
+            },
+        }
+    }
+
+    /// `php_type` as the target PHP version declares it, with the classes it refers to imported
+    fn php_type(&self, php_type: &PhpDataType, imports: &mut Imports) -> PhpDataType {
+        let mut php_type = php_type.map_classes(&mut |class| {
+            // Classes outside the generated ones are fully qualified, enums are not
+            match class.starts_with('\\') {
+                true => imports.name(class),
+                false => imports.name(&self.class_reference(ClassKind::Enum, class)),
+            }
+        });
+        if !self.php.enums {
+            php_type = php_type.without_enums();
+        }
+        if php_type.php_type == PhpType::Mixed && !self.php.mixed_type {
+            php_type.type_hint = String::new();
+        }
+        php_type
+    }
+
+    /// A constant default, with the enum its cases belong to named as it is in the file
+    fn default_value(
+        &self,
+        default_value: &Option<String>,
+        php_type: &PhpDataType,
+        imports: &mut Imports,
+    ) -> Option<String> {
+        let default_value = default_value.as_ref()?;
+        let Some(class) = &php_type.enum_class else {
+            return Some(default_value.clone());
+        };
+        let name = imports.name(&self.class_reference(ClassKind::Enum, class));
+        Some(default_value.replace(&format!("{}::", class), &format!("{}::", name)))
+    }
+
+    /// Every generated class with what it is generated for
+    fn generated_classes(&self) -> Vec<(ClassKind, String, String)> {
+        let mut classes: Vec<(ClassKind, String, String)> = self
+            .enum_classes()
+            .into_iter()
+            .map(|class| (ClassKind::Enum, class.class_name, class.source))
+            .collect();
+        classes.extend(self.engine_data.tables.iter().map(|table| {
+            (
+                ClassKind::Entity,
+                php_class_name(&table.name),
+                format!("table {}", table.name),
+            )
+        }));
+        classes.extend(
+            self.queries
+                .iter()
+                .filter(|query| query.result_table.is_none() && !query.result_columns.is_empty())
+                .map(|query| {
+                    (
+                        ClassKind::Row,
+                        self.row_class_name(query),
+                        format!("the result of query {}", query.name),
+                    )
+                }),
+        );
+        classes.push((
+            ClassKind::Queries,
+            "Queries".to_string(),
+            "the query methods".to_string(),
+        ));
+
+        classes
+    }
+
+    /// Every class generated into the namespace of `kind`
+    fn local_classes(&self, kind: ClassKind) -> Vec<String> {
+        self.generated_classes()
+            .into_iter()
+            .filter(|(class_kind, _, _)| self.layout == OutputLayout::Flat || *class_kind == kind)
+            .map(|(_, class_name, _)| class_name)
+            .collect()
+    }
+
+    /// Namespace classes of `kind` are generated into
+    fn namespace(&self, kind: ClassKind) -> Option<String> {
+        let root = self.namespace.as_deref().filter(|root| !root.is_empty());
+        match (self.layout, root) {
+            (OutputLayout::Flat, _) => self.namespace.clone(),
+            (OutputLayout::Psr4, Some(root)) => Some(format!("{}\\{}", root, kind.sub_namespace())),
+            (OutputLayout::Psr4, None) => Some(kind.sub_namespace().to_string()),
+        }
+    }
+
+    /// How other generated files refer to `class_name` of `kind`: fully qualified when it lives
+    /// in a namespace of its own, so the file imports it
+    fn class_reference(&self, kind: ClassKind, class_name: &str) -> String {
+        match (self.layout, self.namespace(kind)) {
+            (OutputLayout::Psr4, Some(namespace)) => format!("\\{}\\{}", namespace, class_name),
+            _ => class_name.to_string(),
+        }
+    }
+
+    /// File of `class_name` of `kind`, creating its directory where needed
+    fn class_path(&self, kind: ClassKind, class_name: &str) -> Result<PathBuf> {
+        let mut directory = PathBuf::from(&self.output_dir);
+        if self.layout == OutputLayout::Psr4 {
+            directory.push(kind.sub_namespace());
+            fs::create_dir_all(&directory).map_err(|e| {
+                anyhow!(
+                    "Failed to create output directory {}: {}",
+                    directory.display(),
+                    e
+                )
+            })?;
+        }
+
+        Ok(directory.join(format!("{}.php", class_name)))
+    }
+
+    fn row_class_name(&self, query: &AnalyzedQuery) -> String {
+        format!("{}Row", php_class_name(&query.name))
+    }
+}
+
+/// Template view of an analyzed query with the PHP specific names resolved
+#[derive(Serialize)]
+struct QueryContext<'a> {
+    name: &'a str,
+    method_name: String,
+    command: QueryCommand,
+    is_batch: bool,
+    /// Docblock array shape of one set of parameters of a batch query
+    batch_shape: String,
+    /// SQL escaped for a single-quoted PHP string
+    sql: String,
+    params: Vec<QueryParam>,
+    /// Values bound to the placeholders, in order
+    arguments: Vec<Argument>,
+    /// The same for one set of parameters of a batch query
+    batch_arguments: Vec<Argument>,
+    entity_class: Option<String>,
+    return_type: String,
+    return_type_hint: String,
+}
+
+/// A PHP expression bound to a placeholder and the `PDO::PARAM_*` constant it is bound as
+#[derive(Serialize)]
+struct Argument {
+    value: String,
+    pdo_type: &'static str,
+}
+
+/// Template view of a property of an entity or row class
+#[derive(Serialize)]
+struct ColumnContext {
+    #[serde(flatten)]
+    column: ResultColumn,
+    /// The property as `toArray()` returns it, in the form the database takes
+    database_value: String,
+    /// Name of the method of a readonly class returning a copy with the property changed
+    wither: String,
+}
+
+struct EnumClass {
+    class_name: String,
+    values: Vec<String>,
+    /// The type or column the enum is generated for
+    source: String,
+}
+
+#[derive(Serialize)]
+struct EnumCase {
+    name: String,
+    /// Value escaped for a single-quoted PHP string
+    value: String,
+}
+
+/// The PDO type a value of `php_type` is bound as. Binding an integer as a string would make
+/// MySQL's emulated prepares quote it, which `LIMIT ?` does not take.
+fn pdo_type(php_type: &PhpDataType) -> &'static str {
+    match php_type.php_type {
+        PhpType::Int => "PARAM_INT",
+        PhpType::Bool => "PARAM_BOOL",
+        _ => "PARAM_STR",
+    }
+}
+
+/// Converts `variable` of `php_type` into the value the database takes. Enums are written as
+/// their value, sets of them as a comma separated list, decimals as their exact string, dates
+/// in the format of their column, JSON encoded and booleans as 0 or 1.
+fn database_value(php_type: &PhpDataType, variable: &str, php: PhpFeatures) -> String {
+    let method = match &php_type.date_format {
+        _ if php_type.is_enum => Some("value".to_string()),
+        Some(format) => Some(format!("format('{}')", format)),
+        None => None,
+    };
+    let value = if let Some(method) = method {
+        if php_type.is_nullable && php.nullsafe_operator {
+            return format!("{}?->{}", variable, method);
         }
+        format!("{}->{}", variable, method)
+    } else if php_type.is_enum_set {
+        format!("implode(',', array_column({}, 'value'))", variable)
+    } else if php_type.is_set {
+        format!("implode(',', {})", variable)
+    } else if php_type.is_big_decimal {
+        format!("(string) {}", variable)
+    } else if php_type.is_json {
+        format!("json_encode({})", variable)
+    } else if php_type.php_type == PhpType::Bool {
+        format!("(int) {}", variable)
+    } else {
+        return variable.to_string();
+    };
 
-        result
+    if php_type.is_nullable {
+        format!("{} === null ? null : {}", variable, value)
+    } else {
+        value
     }
 }
@@ -0,0 +1,61 @@
+use std::collections::BTreeSet;
+
+/// Classes from outside the namespace of a generated file, such as `\DateTimeImmutable` or
+/// `\PDO`, which the file imports with `use` and then refers to by their short name
+pub struct Imports<'a> {
+    namespace: Option<&'a str>,
+    /// Classes generated into the namespace, which an import must not shadow
+    local_classes: &'a [String],
+    classes: BTreeSet<String>,
+}
+
+impl<'a> Imports<'a> {
+    pub fn new(namespace: Option<&'a str>, local_classes: &'a [String]) -> Self {
+        Self {
+            namespace: namespace.filter(|namespace| !namespace.is_empty()),
+            local_classes,
+            classes: BTreeSet::new(),
+        }
+    }
+
+    /// Name to use for `class` in the file. Fully qualified classes (`\Brick\Math\BigDecimal`)
+    /// are imported, unless their short name is taken, in which case they stay fully
+    /// qualified. Classes of the namespace itself are referred to by their short name.
+    pub fn name(&mut self, class: &str) -> String {
+        let Some(qualified) = class.strip_prefix('\\') else {
+            return class.to_string();
+        };
+        let short = Self::short_name(qualified);
+        if qualified.rsplit_once('\\').map(|(namespace, _)| namespace) == self.namespace {
+            return short.to_string();
+        }
+
+        // Outside a namespace a global class resolves as it is and `use PDO;` only warns
+        if self.namespace.is_none() && !qualified.contains('\\') {
+            return short.to_string();
+        }
+
+        let taken = self
+            .local_classes
+            .iter()
+            .any(|local| local.eq_ignore_ascii_case(short))
+            || self.classes.iter().any(|imported| {
+                imported != qualified && Self::short_name(imported).eq_ignore_ascii_case(short)
+            });
+        if taken {
+            return class.to_string();
+        }
+
+        self.classes.insert(qualified.to_string());
+        short.to_string()
+    }
+
+    /// The `use` statements of the file, sorted
+    pub fn uses(&self) -> Vec<String> {
+        self.classes.iter().cloned().collect()
+    }
+
+    fn short_name(class: &str) -> &str {
+        class.rsplit('\\').next().unwrap_or(class)
+    }
+}
@@ -1,3 +1,7 @@
+mod composer;
 mod engine;
+mod imports;
+mod php_features;
 
 pub use engine::*;
+pub use php_features::*;
@@ -0,0 +1,42 @@
+use crate::configuration::PhpVersion;
+use serde::Serialize;
+
+/// Language features the generated code uses where the target PHP version has them, and falls
+/// back from where it does not. `never`, union type declarations and `#[\Override]` have no
+/// place in it: no generated method ends the script, every type is a single class or nullable,
+/// and no generated class extends another.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct PhpFeatures {
+    /// `public int $id;`, otherwise only the docblock tells the type
+    pub typed_properties: bool,
+    /// `mixed` as a declared type, otherwise the declaration is left out
+    pub mixed_type: bool,
+    /// `$value?->format()`, otherwise a `=== null` check
+    pub nullsafe_operator: bool,
+    /// Backed enums, otherwise classes of string constants named like the cases
+    pub enums: bool,
+    /// `Enum::from(...)`, otherwise `[Enum::class, 'from']`
+    pub first_class_callables: bool,
+    pub readonly_properties: bool,
+    /// `readonly class`, otherwise every promoted property is `readonly` by itself
+    pub readonly_classes: bool,
+    /// `public const int DEFAULT_ID = 1;`
+    pub typed_class_constants: bool,
+}
+
+impl PhpFeatures {
+    /// The compatibility table, the version each feature first shipped in
+    pub fn for_version(version: PhpVersion) -> Self {
+        let since = |major, minor| version >= PhpVersion::new(major, minor);
+        Self {
+            typed_properties: since(7, 4),
+            mixed_type: since(8, 0),
+            nullsafe_operator: since(8, 0),
+            enums: since(8, 1),
+            first_class_callables: since(8, 1),
+            readonly_properties: since(8, 1),
+            readonly_classes: since(8, 2),
+            typed_class_constants: since(8, 3),
+        }
+    }
+}
@@ -1,4 +1,6 @@
 pub mod analyzer;
 pub mod configuration;
+pub mod diagnostics;
 pub mod generator;
 pub mod parser;
+pub mod verifier;
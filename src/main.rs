@@ -1,6 +1,8 @@
-use bde::analyzer::SchemaAnalyzer;
+use bde::analyzer::{QueryAnalyzer, SchemaAnalyzer};
 use bde::configuration::{Args, Commands, get_configuration};
+use bde::diagnostics::into_diagnostics;
 use bde::generator::TemplateEngine;
+use bde::verifier::Verifier;
 use clap::Parser;
 use colored::*;
 use std::path::Path;
@@ -45,44 +47,81 @@ fn main() {
         }
     };
 
+    let packages = match configuration.packages(args.package.as_deref()) {
+        Ok(packages) => packages,
+        Err(e) => {
+            eprintln!("{}", e.to_string().red().bold());
+            process::exit(1);
+        }
+    };
+
     match args.cmd {
         Commands::Generate => {
             println!("Generate");
-            let engine_data = SchemaAnalyzer::analyze_schema(&configuration);
-            match engine_data {
-                Ok(engine_data) => {
-                    let template_engine = TemplateEngine::new(engine_data, &configuration.sql);
+            for package in packages {
+                if let Some(name) = &package.name {
+                    println!("Package {}", name.bold());
+                }
 
-                    match template_engine {
-                        Ok(template_engine) => {
-                            let success = TemplateEngine::generate_templates(&template_engine);
+                let engine_data = SchemaAnalyzer::analyze_schema(package).and_then(|engine_data| {
+                    let queries = QueryAnalyzer::analyze_queries(package, &engine_data)?;
+                    Ok((engine_data, queries))
+                });
+                match engine_data {
+                    Ok((engine_data, queries)) => {
+                        let template_engine = TemplateEngine::new(engine_data, queries, package);
 
-                            match success {
-                                Ok(_) => println!("Success"),
-                                Err(e) => {
-                                    eprintln!(
-                                        "{}: {:?}",
-                                        "Failed to generate php files:".bold().red(),
-                                        e
-                                    )
+                        match template_engine {
+                            Ok(template_engine) => {
+                                let success = TemplateEngine::generate_templates(&template_engine);
+
+                                match success {
+                                    Ok(_) => println!("Success"),
+                                    Err(e) => {
+                                        eprintln!(
+                                            "{}: {:?}",
+                                            "Failed to generate php files:".bold().red(),
+                                            e
+                                        );
+                                        process::exit(1);
+                                    }
                                 }
                             }
+                            Err(e) => {
+                                eprintln!(
+                                    "{}: {}",
+                                    "Failed to create template engine:".bold().red(),
+                                    e.to_string().red().bold()
+                                );
+                                process::exit(1);
+                            }
                         }
-                        Err(e) => {
-                            eprintln!(
-                                "{}: {}",
-                                "Failed to create template engine:".bold().red(),
-                                e.to_string().red().bold()
-                            )
-                        }
+                    }
+                    Err(e) => {
+                        eprintln!("{}", into_diagnostics(e).render(true));
+                        process::exit(1);
                     }
                 }
-                Err(e) => {
-                    eprintln!(
-                        "{}: {}",
-                        "Failed to generate output with error".red().bold(),
-                        e.to_string().red().bold()
-                    )
+            }
+        }
+        Commands::Verify { dsn } => {
+            for package in packages {
+                match Verifier::verify(package, dsn.as_deref()) {
+                    Ok(report) => println!(
+                        "{} {} schema statements and {} queries{}",
+                        "Verified".green().bold(),
+                        report.statements,
+                        report.queries,
+                        package
+                            .name
+                            .as_ref()
+                            .map(|name| format!(" of {}", name))
+                            .unwrap_or_default()
+                    ),
+                    Err(e) => {
+                        eprintln!("{}", into_diagnostics(e).render(true));
+                        process::exit(1);
+                    }
                 }
             }
         }
@@ -0,0 +1,147 @@
+use anyhow::{Result, anyhow};
+use globset::GlobBuilder;
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Resolves files, directories and glob patterns into the SQL files they contain.
+///
+/// Entries are taken in the order they are listed. Directories are searched recursively and
+/// their files, like the matches of a glob, are sorted naturally by path so numbered
+/// migrations (`2_add_name.sql` before `10_add_index.sql`) are applied in order. A file
+/// listed by more than one entry is only returned the first time.
+pub fn collect_sql_files(paths: &[String]) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = Vec::new();
+
+    for entry in paths {
+        let mut found = if is_glob(entry) {
+            glob_files(entry)?
+        } else {
+            let path = Path::new(entry);
+            if path.is_dir() {
+                directory_files(path)?
+            } else if path.is_file() {
+                vec![path.to_path_buf()]
+            } else {
+                return Err(anyhow!("Path {} does not exist", entry));
+            }
+        };
+        found.sort_by(|a, b| natural_cmp(&a.to_string_lossy(), &b.to_string_lossy()));
+
+        for file in found {
+            if !files.contains(&file) {
+                files.push(file);
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+fn is_glob(path: &str) -> bool {
+    path.contains(['*', '?', '[', '{'])
+}
+
+fn is_sql_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.to_string_lossy().to_lowercase() == "sql")
+}
+
+fn directory_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in WalkDir::new(dir) {
+        let entry =
+            entry.map_err(|e| anyhow!("Error reading directory {}: {:?}", dir.display(), e))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        if is_sql_file(entry.path()) {
+            files.push(entry.into_path());
+        } else {
+            println!("Skipping non-SQL file: {}", entry.path().display());
+        }
+    }
+    Ok(files)
+}
+
+/// Matches a pattern such as `migrations/**/*.sql`, where `*` stays within a directory
+fn glob_files(pattern: &str) -> Result<Vec<PathBuf>> {
+    let matcher = GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .build()
+        .map_err(|e| anyhow!("Invalid pattern {}: {}", pattern, e))?
+        .compile_matcher();
+
+    // Only walk the part of the pattern before the first wildcard
+    let base: PathBuf = Path::new(pattern)
+        .components()
+        .take_while(|component| !is_glob(&component.as_os_str().to_string_lossy()))
+        .collect();
+    let base = if base.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        base
+    };
+
+    let mut files = Vec::new();
+    for entry in WalkDir::new(&base)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+    {
+        let path = entry.path();
+        let candidate = path.strip_prefix("./").unwrap_or(path);
+        if entry.file_type().is_file() && is_sql_file(path) && matcher.is_match(candidate) {
+            files.push(candidate.to_path_buf());
+        }
+    }
+
+    if files.is_empty() {
+        return Err(anyhow!("No SQL files match {}", pattern));
+    }
+    Ok(files)
+}
+
+/// Compares strings treating runs of digits as numbers, so `2` sorts before `10`
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a = a.chars().peekable();
+    let mut b = b.chars().peekable();
+
+    loop {
+        match (a.peek(), b.peek()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let x = take_number(&mut a);
+                let y = take_number(&mut b);
+                // Compare by value, then leading zeros, without overflowing on long runs
+                let ordering = x
+                    .trim_start_matches('0')
+                    .len()
+                    .cmp(&y.trim_start_matches('0').len())
+                    .then_with(|| x.trim_start_matches('0').cmp(y.trim_start_matches('0')))
+                    .then_with(|| x.len().cmp(&y.len()));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(x), Some(y)) => {
+                let ordering = x.cmp(y);
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
+
+fn take_number(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut number = String::new();
+    while let Some(c) = chars.next_if(char::is_ascii_digit) {
+        number.push(c);
+    }
+    number
+}
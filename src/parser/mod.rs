@@ -1,5 +1,9 @@
+mod files;
 pub mod query;
 pub mod schema;
+mod source;
 
+pub use files::*;
 pub use query::*;
 pub use schema::*;
+pub use source::*;
@@ -1,85 +1,125 @@
+use crate::configuration::Engine;
+use crate::diagnostics::{Diagnostic, Diagnostics, into_diagnostics};
+use crate::parser::{SourceText, collect_sql_files};
 use SetExpr::Select;
 use anyhow::{Result, anyhow};
+use serde::Serialize;
 use sqlparser::ast::{SetExpr, Statement};
-use sqlparser::dialect::MySqlDialect;
+use sqlparser::dialect::Dialect;
 use sqlparser::parser::Parser as SqlParser;
+use sqlparser::tokenizer::{Span, Token, TokenWithSpan, Tokenizer};
+use std::fmt;
 use std::fs;
+use std::path::Path;
+use std::str::FromStr;
 
 pub struct QuerySqlFileParser {
-    dialect: MySqlDialect,
+    engine: Engine,
+    dialect: Box<dyn Dialect>,
 }
 
 impl Default for QuerySqlFileParser {
     fn default() -> Self {
-        Self {
-            dialect: MySqlDialect {},
-        }
+        Self::new(Engine::default())
     }
 }
 
 impl QuerySqlFileParser {
-    /// Reads and parses SQL files from a directory
-    pub fn parse_directory(&self, dir_path: &str) -> Result<Vec<QuerySqlFile>> {
-        println!("Parsing SQL files in {}", dir_path);
-        let entries = fs::read_dir(dir_path)
-            .map_err(|e| anyhow!("Error reading directory {}: {:?}", dir_path, e))?;
+    pub fn new(engine: Engine) -> Self {
+        Self {
+            engine,
+            dialect: engine.dialect(),
+        }
+    }
 
-        let mut sql_files = Vec::new();
+    /// Reads and parses the SQL files of a directory and its subdirectories
+    pub fn parse_directory(&self, dir_path: &str) -> Result<Vec<QuerySqlFile>> {
+        self.parse_paths(&[dir_path.to_string()])
+    }
 
-        for entry_result in entries {
-            let entry = entry_result
-                .map_err(|e| anyhow!("Error reading directory entry {}: {:?}", dir_path, e))?;
+    /// Reads and parses SQL files from files, directories and glob patterns, in the order
+    /// `collect_sql_files` resolves them to
+    pub fn parse_paths(&self, paths: &[String]) -> Result<Vec<QuerySqlFile>> {
+        println!("Parsing SQL files in {}", paths.join(", "));
 
-            let path = entry.path();
+        let mut sql_files = Vec::new();
+        let mut diagnostics = Diagnostics::default();
 
-            if let Some(extension) = path.extension() {
-                if extension.to_string_lossy().to_lowercase() == "sql" {
-                    println!("Processing SQL file: {}", path.display());
-                    // let sql_file = self.parse_file(&path)?;
-                    // sql_files.push(sql_file);
-                } else {
-                    println!("Skipping non-SQL file: {}", path.display());
-                }
-            } else {
-                println!("Skipping file with no extension: {}", path.display());
+        for path in collect_sql_files(paths)? {
+            println!("Processing SQL file: {}", path.display());
+            match self.parse_file(&path) {
+                Ok(sql_file) => sql_files.push(sql_file),
+                Err(e) => diagnostics.push_error(e),
             }
         }
 
+        diagnostics.into_result()?;
+
         if sql_files.is_empty() {
-            return Err(anyhow!("No SQL files found in {}", dir_path));
+            return Err(anyhow!("No SQL files found in {}", paths.join(", ")));
         }
 
         Ok(sql_files)
     }
 
     /// Parses a single SQL file
-    // pub fn parse_file(&self, path: &Path) -> Result<QuerySqlFile> {
-    //     let content = fs::read_to_string(path)
-    //         .map_err(|e| anyhow!("Error reading file {}: {:?}", path.display(), e))?;
-    //
-    //     let statements = self.parse_sql(&content)?;
-    //
-    //     Ok(QuerySqlFile {
-    //         path: path.to_string_lossy().to_string(),
-    //         queries: vec![QueriesMap { statements }],
-    //     })
-    // }
+    pub fn parse_file(&self, path: &Path) -> Result<QuerySqlFile> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Error reading file {}: {:?}", path.display(), e))?;
+
+        let path = path.to_string_lossy().to_string();
+        let queries = self.parse_content(&content).map_err(|e| {
+            into_diagnostics(e).map(|diagnostic| diagnostic.in_source(&path, &content))
+        })?;
+
+        Ok(QuerySqlFile {
+            path,
+            content,
+            queries,
+        })
+    }
+
+    /// Splits annotated SQL content into named queries and parses each of them, reporting
+    /// the problems of every query rather than only the first
+    pub fn parse_content(&self, content: &str) -> Result<Vec<QueriesMap>> {
+        let mut queries = Vec::new();
+        let mut diagnostics = Diagnostics::default();
+        for (line, query) in self.split_queries(content) {
+            match self.parse_query(&query, line) {
+                Ok(query) => queries.push(query),
+                Err(e) => diagnostics.push_error(e),
+            }
+        }
+        diagnostics.into_result()?;
+        Ok(queries)
+    }
 
     pub fn divide_content_into_queries(&self, sql: &str) -> Result<Vec<String>> {
+        Ok(self
+            .split_queries(sql)
+            .into_iter()
+            .map(|(_, query)| query)
+            .collect())
+    }
+
+    /// Splits content on `-- name:` annotations, keeping the line each query starts on
+    fn split_queries(&self, sql: &str) -> Vec<(usize, String)> {
         let mut queries = Vec::new();
         let mut current_query = String::new();
+        let mut current_line = 0;
         let mut in_query = false;
 
-        for line in sql.lines() {
+        for (index, line) in sql.lines().enumerate() {
             let trimmed = line.trim();
             if trimmed.starts_with("-- name:") {
                 if in_query && !current_query.trim().is_empty() {
-                    queries.push(current_query.trim().to_string());
+                    queries.push((current_line, current_query.trim().to_string()));
                     current_query = String::new();
                 }
 
                 current_query.push_str(line);
                 current_query.push('\n');
+                current_line = index + 1;
                 in_query = true;
             } else if in_query {
                 current_query.push_str(line);
@@ -88,17 +128,169 @@ impl QuerySqlFileParser {
         }
 
         if in_query && !current_query.trim().is_empty() {
-            queries.push(current_query.trim().to_string());
+            queries.push((current_line, current_query.trim().to_string()));
         }
 
-        Ok(queries)
+        queries
+    }
+
+    /// Parses a single `-- name: <Name> :<command>` block starting at `line`
+    pub fn parse_query(&self, query: &str, line: usize) -> Result<QueriesMap> {
+        let (annotation, body) = query.split_once('\n').unwrap_or((query, ""));
+
+        let mut parts = annotation
+            .trim()
+            .trim_start_matches("-- name:")
+            .split_whitespace();
+        let at_annotation = |message: String, text: &str| {
+            let column = annotation.find(text).unwrap_or(0) + 1;
+            Diagnostic::at(message, line, column, text.len())
+        };
+
+        let name = parts
+            .next()
+            .ok_or_else(|| {
+                at_annotation("query annotation is missing a name".to_string(), annotation)
+            })?
+            .to_string();
+        let command = parts.next().ok_or_else(|| {
+            at_annotation(
+                format!("query {} is missing a command like :one or :many", name),
+                &name,
+            )
+        })?;
+        let command = command
+            .strip_prefix(':')
+            .unwrap_or(command)
+            .parse::<QueryCommand>()
+            .map_err(|e| at_annotation(format!("query {}: {}", name, e), command))?;
+
+        let statements = SqlParser::parse_sql(&*self.dialect, body).map_err(|e| {
+            Diagnostic::from_parser_error(&e)
+                .context(format!("failed to parse query {}", name))
+                .shift(line)
+        })?;
+        if statements.len() != 1 {
+            return Err(at_annotation(
+                format!(
+                    "query {} must contain exactly one statement, found {}",
+                    name,
+                    statements.len()
+                ),
+                &name,
+            )
+            .into());
+        }
+
+        let (sql, placeholders, placeholder_offsets) = self
+            .normalize_placeholders(body)
+            .map_err(|e| into_diagnostics(e).map(|diagnostic| diagnostic.shift(line)))?;
+
+        Ok(QueriesMap {
+            name,
+            command,
+            line,
+            numbered_sql: placeholder_offsets.number(&sql),
+            sql,
+            placeholder_offsets,
+            placeholder_locations: placeholders.iter().map(|(_, location)| *location).collect(),
+            placeholders: placeholders
+                .into_iter()
+                .map(|(placeholder, _)| placeholder)
+                .collect(),
+            statements,
+        })
+    }
+
+    /// Rewrites every placeholder (`:name`, `?`, `$1`) into a positional `?` and collapses
+    /// whitespace and comments, returning the rewritten SQL along with the original
+    /// placeholders and their line and column in the order PDO will bind them, and where the
+    /// rewritten SQL has them
+    pub fn normalize_placeholders(
+        &self,
+        sql: &str,
+    ) -> Result<(String, Placeholders, PlaceholderOffsets)> {
+        let tokens = Tokenizer::new(&*self.dialect, sql)
+            .tokenize_with_location()
+            .map_err(|e| anyhow!("Failed to tokenize SQL with error: {:?}", e))?;
+
+        let source = SourceText::new(sql);
+        let mut normalized = String::new();
+        let mut placeholders = Vec::new();
+        let mut offsets = PlaceholderOffsets::default();
+        let mut tokens = tokens.into_iter().peekable();
+
+        while let Some(TokenWithSpan { token, span }) = tokens.next() {
+            match token {
+                Token::Placeholder(placeholder) => {
+                    self.check_placeholder(&placeholder, span)?;
+                    offsets.placeholders.push(normalized.len());
+                    normalized.push('?');
+                    placeholders.push((placeholder, (span.start.line, span.start.column)));
+                }
+                Token::Colon if matches!(tokens.peek().map(|t| &t.token), Some(Token::Word(_))) => {
+                    if let Some(Token::Word(word)) = tokens.next().map(|t| t.token) {
+                        offsets.placeholders.push(normalized.len());
+                        normalized.push('?');
+                        placeholders.push((
+                            format!(":{}", word.value),
+                            (span.start.line, span.start.column),
+                        ));
+                    }
+                }
+                // Other dialects read `$1` as a name, point out what it was meant to be
+                Token::Word(word) if word.quote_style.is_none() && is_numbered(&word.value) => {
+                    self.check_placeholder(&word.value, span)?;
+                }
+                // PDO takes every bare `?` for a placeholder, PostgreSQL's jsonb operators
+                // have to be escaped by doubling it
+                Token::Question | Token::QuestionAnd | Token::QuestionPipe => {
+                    offsets.escapes.push(normalized.len());
+                    normalized.push('?');
+                    normalized.push_str(source.slice(span));
+                }
+                Token::Whitespace(_) => {
+                    if !normalized.is_empty() && !normalized.ends_with(' ') {
+                        normalized.push(' ');
+                    }
+                }
+                Token::SemiColon => {}
+                _ => normalized.push_str(source.slice(span)),
+            }
+        }
+
+        Ok((normalized.trim_end().to_string(), placeholders, offsets))
+    }
+
+    /// Rejects positional placeholders the configured engine does not use
+    fn check_placeholder(&self, placeholder: &str, span: Span) -> Result<()> {
+        let (supported, expected) = match self.engine {
+            Engine::MySql => (placeholder == "?", "? or :name"),
+            Engine::PostgreSql => (is_numbered(placeholder), "$1 or :name"),
+            Engine::Sqlite => (
+                placeholder == "?" || is_numbered(placeholder),
+                "?, ?1 or :name",
+            ),
+        };
+        if supported {
+            return Ok(());
+        }
+
+        Err(Diagnostic::at_span(
+            format!(
+                "placeholder {} is not supported by {}, use {}",
+                placeholder, self.engine, expected
+            ),
+            span,
+        )
+        .into())
     }
 
     /// Parses a SQL string into AST statements
-    pub fn parse_sql(&self, queries: &Vec<String>) -> Result<Vec<Vec<Statement>>> {
+    pub fn parse_sql(&self, queries: &[String]) -> Result<Vec<Vec<Statement>>> {
         let mut statements = Vec::new();
         for sql in queries {
-            let parsed_statement = SqlParser::parse_sql(&self.dialect, sql.as_str())
+            let parsed_statement = SqlParser::parse_sql(&*self.dialect, sql.as_str())
                 .map_err(|e| anyhow!("Failed to parse SQL with error: {:?}", e))?;
 
             statements.push(parsed_statement)
@@ -107,16 +299,171 @@ impl QuerySqlFileParser {
     }
 }
 
+/// Line and column of a token, both counting from 1
+pub type LineColumn = (u64, u64);
+
+/// Placeholders as written with their line and column, in binding order
+pub type Placeholders = Vec<(String, LineColumn)>;
+
 /// Represents a parsed SQL file
 pub struct QuerySqlFile {
     pub path: String,
+    pub content: String,
     pub queries: Vec<QueriesMap>,
 }
 
+/// A single named query from a query file
 pub struct QueriesMap {
+    pub name: String,
+    pub command: QueryCommand,
+    /// Line of the `-- name:` annotation
+    pub line: usize,
+    /// SQL with every placeholder rewritten to a positional `?`
+    pub sql: String,
+    /// The same SQL with `$1`, `$2`, ... in binding order, the way PostgreSQL prepares it
+    pub numbered_sql: String,
+    /// Original placeholders in binding order
+    pub placeholders: Vec<String>,
+    /// Line and column of each placeholder within the query's SQL, in the same order
+    pub placeholder_locations: Vec<LineColumn>,
+    /// Where `sql` has its placeholders
+    pub placeholder_offsets: PlaceholderOffsets,
     pub statements: Vec<Statement>,
 }
 
+/// What a generated query method does with its statement, from the `:command` suffix
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QueryCommand {
+    /// Returns the first row or null
+    One,
+    /// Returns every row
+    Many,
+    /// Returns nothing
+    Exec,
+    /// Returns the number of affected rows
+    ExecRows,
+    /// Returns the id generated by the statement
+    ExecLastId,
+    /// Returns the executed `PDOStatement`
+    ExecResult,
+    /// Executes the statement once for every set of parameters
+    BatchExec,
+    /// Returns the first row for every set of parameters
+    BatchOne,
+}
+
+impl QueryCommand {
+    pub fn returns_rows(&self) -> bool {
+        matches!(
+            self,
+            QueryCommand::One | QueryCommand::Many | QueryCommand::BatchOne
+        )
+    }
+
+    pub fn is_batch(&self) -> bool {
+        matches!(self, QueryCommand::BatchExec | QueryCommand::BatchOne)
+    }
+}
+
+impl FromStr for QueryCommand {
+    type Err = anyhow::Error;
+
+    fn from_str(command: &str) -> Result<Self> {
+        match command {
+            "one" => Ok(QueryCommand::One),
+            "many" => Ok(QueryCommand::Many),
+            "exec" => Ok(QueryCommand::Exec),
+            "execrows" => Ok(QueryCommand::ExecRows),
+            "execlastid" => Ok(QueryCommand::ExecLastId),
+            "execresult" => Ok(QueryCommand::ExecResult),
+            "batchexec" => Ok(QueryCommand::BatchExec),
+            "batchone" => Ok(QueryCommand::BatchOne),
+            _ => Err(anyhow!(
+                "unknown command :{}, expected one of :one, :many, :exec, :execrows, \
+                 :execlastid, :execresult, :batchexec, :batchone",
+                command
+            )),
+        }
+    }
+}
+
+impl fmt::Display for QueryCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let command = match self {
+            QueryCommand::One => "one",
+            QueryCommand::Many => "many",
+            QueryCommand::Exec => "exec",
+            QueryCommand::ExecRows => "execrows",
+            QueryCommand::ExecLastId => "execlastid",
+            QueryCommand::ExecResult => "execresult",
+            QueryCommand::BatchExec => "batchexec",
+            QueryCommand::BatchOne => "batchone",
+        };
+        write!(f, ":{}", command)
+    }
+}
+
+/// Byte offsets of the placeholders in normalized SQL, taken from its tokens. Scanning the SQL
+/// for `?` again would take one in a literal for a placeholder, like in PostgreSQL's
+/// `$$why?$$` or MySQL's `'it\'s?'`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlaceholderOffsets {
+    /// The `?` of every placeholder, in binding order
+    pub placeholders: Vec<usize>,
+    /// The `?` doubling a `?` operator for PDO
+    pub escapes: Vec<usize>,
+}
+
+impl PlaceholderOffsets {
+    /// `sql` with its placeholders numbered `$1`, `$2`, ... and its `??` operators unescaped,
+    /// the way PostgreSQL prepares it
+    pub fn number(&self, sql: &str) -> String {
+        self.replace_unescaped(sql, |number| format!("${}", number))
+    }
+
+    /// Replaces every placeholder of `sql` with what `replace` returns for its number,
+    /// counting from 1 in binding order
+    pub fn replace(&self, sql: &str, replace: impl FnMut(usize) -> String) -> String {
+        self.rewrite(sql, false, replace)
+    }
+
+    /// The same with the `??` operators unescaped, for SQL that does not go through PDO
+    pub fn replace_unescaped(&self, sql: &str, replace: impl FnMut(usize) -> String) -> String {
+        self.rewrite(sql, true, replace)
+    }
+
+    fn rewrite(
+        &self,
+        sql: &str,
+        unescape: bool,
+        mut replace: impl FnMut(usize) -> String,
+    ) -> String {
+        let mut rewritten = String::new();
+        let mut count = 0;
+        for (offset, c) in sql.char_indices() {
+            if self.placeholders.binary_search(&offset).is_ok() {
+                count += 1;
+                rewritten.push_str(&replace(count));
+            } else if !unescape || self.escapes.binary_search(&offset).is_err() {
+                rewritten.push(c);
+            }
+        }
+        rewritten
+    }
+}
+
+/// Number of a numbered placeholder, like PostgreSQL's `$1` or SQLite's `?1`
+pub fn placeholder_number(placeholder: &str) -> Option<usize> {
+    placeholder
+        .strip_prefix(['$', '?'])
+        .and_then(|number| number.parse().ok())
+}
+
+fn is_numbered(placeholder: &str) -> bool {
+    placeholder_number(placeholder).is_some()
+}
+
 pub fn debug_statement_structure(statement: &Statement) {
     // println!("Full statement: {:#?}", statement);
 
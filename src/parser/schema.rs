@@ -1,52 +1,61 @@
+use crate::configuration::Engine;
+use crate::diagnostics::{Diagnostic, Diagnostics, into_diagnostics};
+use crate::parser::{SourceText, collect_sql_files};
 use anyhow::{Result, anyhow};
 use sqlparser::ast::Statement;
-use sqlparser::dialect::MySqlDialect;
+use sqlparser::dialect::Dialect;
 use sqlparser::parser::Parser as SqlParser;
+use sqlparser::tokenizer::{Token, TokenWithSpan, Tokenizer};
 use std::fs;
 use std::path::Path;
 
 pub struct SqlFileParser {
-    dialect: MySqlDialect,
+    dialect: Box<dyn Dialect>,
 }
 
 impl Default for SqlFileParser {
     fn default() -> Self {
-        Self {
-            dialect: MySqlDialect {},
-        }
+        Self::new(Engine::default())
     }
 }
 
 impl SqlFileParser {
-    /// Reads and parses SQL files from a directory
+    pub fn new(engine: Engine) -> Self {
+        Self {
+            dialect: engine.dialect(),
+        }
+    }
+
+    /// Reads and parses the SQL files of a directory and its subdirectories
     pub fn parse_directory(&self, dir_path: &str) -> Result<Vec<SqlFile>> {
-        println!("Parsing SQL files in {}", dir_path);
-        let entries = fs::read_dir(dir_path)
-            .map_err(|e| anyhow!("Error reading directory {}: {:?}", dir_path, e))?;
+        self.parse_paths(&[dir_path.to_string()])
+    }
 
-        let mut sql_files = Vec::new();
+    /// Reads and parses SQL files from files, directories and glob patterns, in the order
+    /// `collect_sql_files` resolves them to
+    pub fn parse_paths(&self, paths: &[String]) -> Result<Vec<SqlFile>> {
+        println!("Parsing SQL files in {}", paths.join(", "));
 
-        for entry_result in entries {
-            let entry = entry_result
-                .map_err(|e| anyhow!("Error reading directory entry {}: {:?}", dir_path, e))?;
+        let mut sql_files = Vec::new();
+        let mut diagnostics = Diagnostics::default();
 
-            let path = entry.path();
+        for path in collect_sql_files(paths)? {
+            if is_down_migration(&path) {
+                println!("Skipping down migration: {}", path.display());
+                continue;
+            }
 
-            if let Some(extension) = path.extension() {
-                if extension.to_string_lossy().to_lowercase() == "sql" {
-                    println!("Processing SQL file: {}", path.display());
-                    let sql_file = self.parse_file(&path)?;
-                    sql_files.push(sql_file);
-                } else {
-                    println!("Skipping non-SQL file: {}", path.display());
-                }
-            } else {
-                println!("Skipping file with no extension: {}", path.display());
+            println!("Processing SQL file: {}", path.display());
+            match self.parse_file(&path) {
+                Ok(sql_file) => sql_files.push(sql_file),
+                Err(e) => diagnostics.push_error(e),
             }
         }
 
+        diagnostics.into_result()?;
+
         if sql_files.is_empty() {
-            return Err(anyhow!("No SQL files found in {}", dir_path));
+            return Err(anyhow!("No SQL files found in {}", paths.join(", ")));
         }
 
         Ok(sql_files)
@@ -57,23 +66,102 @@ impl SqlFileParser {
         let content = fs::read_to_string(path)
             .map_err(|e| anyhow!("Error reading file {}: {:?}", path.display(), e))?;
 
-        let statements = self.parse_sql(&content)?;
+        let path = path.to_string_lossy().to_string();
+        let statements = self.parse_sql(&content).map_err(|e| {
+            into_diagnostics(e).map(|diagnostic| diagnostic.in_source(&path, &content))
+        })?;
 
         Ok(SqlFile {
-            path: path.to_string_lossy().to_string(),
+            path,
+            content,
             statements,
         })
     }
 
-    /// Parses a SQL string into AST statements
+    /// Parses a SQL string into AST statements, leaving out the down sections of migrations
     pub fn parse_sql(&self, sql: &str) -> Result<Vec<Statement>> {
-        SqlParser::parse_sql(&self.dialect, sql)
-            .map_err(|e| anyhow!("Failed to parse SQL with error: {:?}", e))
+        SqlParser::parse_sql(&*self.dialect, &up_migration(sql)).map_err(|e| {
+            Diagnostic::from_parser_error(&e)
+                .context("failed to parse SQL")
+                .into()
+        })
+    }
+}
+
+impl SqlFileParser {
+    /// Splits SQL into its statements, returning the line each of them starts on with its
+    /// text, so they can be run one by one against a database
+    pub fn split_statements(&self, sql: &str) -> Result<Vec<(usize, String)>> {
+        let sql = up_migration(sql);
+        let tokens = Tokenizer::new(&*self.dialect, &sql)
+            .tokenize_with_location()
+            .map_err(|e| anyhow!("Failed to tokenize SQL with error: {:?}", e))?;
+        let source = SourceText::new(&sql);
+
+        let mut statements = Vec::new();
+        let mut current = String::new();
+        let mut start = None;
+
+        for TokenWithSpan { token, span } in tokens {
+            match token {
+                Token::SemiColon => {
+                    if let Some(line) = start.take() {
+                        statements.push((line, current.trim().to_string()));
+                    }
+                    current.clear();
+                }
+                // Leading whitespace and comments are not part of the statement
+                Token::Whitespace(_) if start.is_none() => {}
+                _ => {
+                    start.get_or_insert(span.start.line as usize);
+                    current.push_str(source.slice(span));
+                }
+            }
+        }
+
+        if let Some(line) = start {
+            statements.push((line, current.trim().to_string()));
+        }
+
+        Ok(statements)
+    }
+}
+
+/// golang-migrate keeps rollbacks in their own `*.down.sql` files
+fn is_down_migration(path: &Path) -> bool {
+    path.to_string_lossy().to_lowercase().ends_with(".down.sql")
+}
+
+/// Blanks out the rollback sections of dbmate (`-- migrate:up` / `-- migrate:down`) and
+/// goose (`-- +goose Up` / `-- +goose Down`) migrations. Lines are kept, so locations still
+/// point into the original file. SQL without markers is returned as it is.
+pub fn up_migration(sql: &str) -> String {
+    let mut up = true;
+    sql.lines()
+        .map(|line| {
+            if let Some(marker) = migration_marker(line) {
+                up = marker;
+            }
+            if up { line } else { "" }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Whether a line starts an up (`true`) or down (`false`) section
+fn migration_marker(line: &str) -> Option<bool> {
+    let comment = line.trim().strip_prefix("--")?.trim().to_lowercase();
+    let mut words = comment.split_whitespace();
+    match (words.next()?, words.next()) {
+        ("migrate:up", _) | ("+goose", Some("up")) => Some(true),
+        ("migrate:down", _) | ("+goose", Some("down")) => Some(false),
+        _ => None,
     }
 }
 
 /// Represents a parsed SQL file
 pub struct SqlFile {
     pub path: String,
+    pub content: String,
     pub statements: Vec<Statement>,
 }
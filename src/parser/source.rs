@@ -0,0 +1,37 @@
+use sqlparser::tokenizer::{Location, Span};
+
+/// SQL text indexed by line, to copy tokens through as they were written. Re-serialising a
+/// token loses how it was spelled, such as the escaped quote in `'O''Brien'`.
+pub struct SourceText<'a> {
+    source: &'a str,
+    /// Byte offset every line starts at
+    lines: Vec<usize>,
+}
+
+impl<'a> SourceText<'a> {
+    pub fn new(source: &'a str) -> Self {
+        let lines = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(offset, _)| offset + 1))
+            .collect();
+        Self { source, lines }
+    }
+
+    /// The text a token was read from
+    pub fn slice(&self, span: Span) -> &'a str {
+        &self.source[self.offset(span.start)..self.offset(span.end)]
+    }
+
+    /// Byte offset of a location, whose line and column count from 1 and columns in chars
+    fn offset(&self, location: Location) -> usize {
+        let Some(&start) = self.lines.get((location.line as usize).saturating_sub(1)) else {
+            return self.source.len();
+        };
+        let line = &self.source[start..];
+        let column = (location.column as usize).saturating_sub(1);
+        start
+            + line
+                .char_indices()
+                .nth(column)
+                .map_or(line.len(), |(offset, _)| offset)
+    }
+}
@@ -0,0 +1,5 @@
+mod database;
+mod verify;
+
+pub use database::*;
+pub use verify::*;
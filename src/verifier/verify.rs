@@ -0,0 +1,83 @@
+use crate::analyzer::{QueryAnalyzer, SchemaAnalyzer};
+use crate::configuration::SqlSettings;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::parser::{QuerySqlFileParser, SqlFileParser};
+use crate::verifier::scratch_database;
+use anyhow::Result;
+
+pub struct Verifier;
+
+/// What a successful verification checked
+pub struct VerifyReport {
+    pub statements: usize,
+    pub queries: usize,
+}
+
+impl Verifier {
+    /// Applies every schema file to a scratch database and prepares every query against it,
+    /// in the form the generated method prepares it. Statements the database rejects are
+    /// reported together, pointing at their source.
+    pub fn verify(config: &SqlSettings, dsn: Option<&str>) -> Result<VerifyReport> {
+        let engine = config.engine;
+        let dsn = dsn.or(config.verify.dsn.as_deref());
+
+        let schema_parser = SqlFileParser::new(engine);
+        let sql_files = schema_parser.parse_paths(&config.schemas)?;
+        let query_files = QuerySqlFileParser::new(engine).parse_directory(&config.queries)?;
+
+        let mut database = scratch_database(engine, dsn)?;
+        println!("Verifying against a scratch {} database", engine);
+
+        let mut report = VerifyReport {
+            statements: 0,
+            queries: 0,
+        };
+        let mut diagnostics = Diagnostics::default();
+
+        for sql_file in &sql_files {
+            for (line, statement) in schema_parser.split_statements(&sql_file.content)? {
+                report.statements += 1;
+                if let Err(message) = database.execute(&statement) {
+                    let width = statement.lines().next().unwrap_or_default().len();
+                    diagnostics.push(
+                        Diagnostic::at(message, line, 1, width)
+                            .context("database rejected statement")
+                            .in_source(&sql_file.path, &sql_file.content),
+                    );
+                }
+            }
+        }
+
+        // A query the analysis rejects generates no method, the database still checks what
+        // was written
+        let engine_data = SchemaAnalyzer::analyze_schema(config).ok();
+        for query_file in &query_files {
+            for query in &query_file.queries {
+                report.queries += 1;
+                let analyzed = engine_data
+                    .as_ref()
+                    .and_then(|engine_data| QueryAnalyzer::analyze_query(engine_data, query).ok());
+                let (sql, numbered_sql) = match &analyzed {
+                    Some(analyzed) => (&analyzed.sql, &analyzed.numbered_sql),
+                    None => (&query.sql, &query.numbered_sql),
+                };
+                if let Err(message) = database.prepare(sql, numbered_sql) {
+                    diagnostics.push(
+                        Diagnostic::at(
+                            message,
+                            query.line,
+                            "-- name: ".len() + 1,
+                            query.name.len(),
+                        )
+                        .context(format!("database rejected query {}", query.name))
+                        .in_source(&query_file.path, &query_file.content),
+                    );
+                }
+            }
+        }
+
+        diagnostics.into_result()?;
+
+        Ok(report)
+    }
+}
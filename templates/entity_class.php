@@ -1,4 +1,4 @@
-<?php
+{%- import "macros.php" as macros %}<?php
 /**
  * This file is auto-generated by BDE (Boring Database Engine) v0.1.0.
  * Do not edit this file directly.
@@ -13,12 +13,40 @@
 namespace {{ namespace }};
 
 {% endif %}
-class {{ class_name }}
+{%- for use in uses %}
+use {{ use }};
+{%- endfor %}
+{% if uses %}
+{% endif %}class {{ class_name }}
 {
+    {%- if with_defaults %}{% for column in columns %}{% if column.default_value %}
+    public const {% if php.typed_class_constants %}{{ column.php_type.simple_type }} {% endif %}DEFAULT_{{ column.name | upper }} = {{ column.default_value }};
+    {%- endif %}{% endfor %}{% endif %}
     {% for column in columns %}
     /**
-     * @var {{ column.data_type.php_type.docblock_type }}
+     * @var {{ column.php_type.docblock_type }}
      */
-    public {{ column.data_type.php_type.type_hint }} ${{ column.name }};
+    public {% if php.typed_properties and column.php_type.type_hint %}{{ column.php_type.type_hint }} {% endif %}${{ column.name }}{% if with_defaults and column.default_value %} = self::DEFAULT_{{ column.name | upper }}{% endif %};
     {% endfor %}
+    /**
+     * @param array<string, mixed> $row
+     */
+    public static function fromRow(array $row): self
+    {
+{{ macros::hydrate(target="entity", class="self", columns=columns, indent="        ", php=php) }}
+
+        return $entity;
+    }
+
+    /**
+     * @return array<string, mixed>
+     */
+    public function toArray(): array
+    {
+        return [
+        {%- for column in columns %}
+            '{{ column.name }}' => {{ column.database_value }},
+        {%- endfor %}
+        ];
+    }
 }
\ No newline at end of file
@@ -0,0 +1,30 @@
+<?php
+/**
+ * This file is auto-generated by BDE (Boring Database Engine) v0.1.0.
+ * Do not edit this file directly.
+ *
+ * To regenerate this file, run:
+ * bde generate
+ *
+ * @generated
+ */
+{% if namespace is defined and namespace is string and namespace != "" %}
+
+namespace {{ namespace }};
+
+{% endif %}
+{%- for use in uses %}
+use {{ use }};
+{%- endfor %}
+{% if uses %}
+{% endif %}{% if php.enums %}enum {{ class_name }}: string
+{
+    {%- for case in cases %}
+    case {{ case.name }} = '{{ case.value }}';
+    {%- endfor %}
+}{% else %}final class {{ class_name }}
+{
+    {%- for case in cases %}
+    public const {{ case.name }} = '{{ case.value }}';
+    {%- endfor %}
+}{% endif %}
\ No newline at end of file
@@ -0,0 +1,10 @@
+{% macro row_value(column, php) -%}
+{% if column.php_type.is_nullable %}$row['{{ column.name }}'] === null ? null : {% endif %}{% if column.php_type.simple_type == "int" %}(int) {% elif column.php_type.simple_type == "float" %}(float) {% endif %}{% if column.php_type.date_format %}{% if column.php_type.date_format_exact %}{{ column.php_type.simple_type }}::createFromFormat('!{{ column.php_type.date_format }}', $row['{{ column.name }}']){% else %}new {{ column.php_type.simple_type }}($row['{{ column.name }}']){% endif %}{% elif column.php_type.simple_type == "bool" %}in_array($row['{{ column.name }}'], [true, 1, '1', 't', 'true'], true){% elif column.php_type.is_json %}json_decode($row['{{ column.name }}'], true){% elif column.php_type.is_big_decimal %}{{ column.php_type.simple_type }}::of($row['{{ column.name }}']){% elif column.php_type.is_enum %}{{ column.php_type.simple_type }}::from($row['{{ column.name }}']){% elif column.php_type.is_enum_set %}($row['{{ column.name }}'] === '' ? [] : array_map({% if php.first_class_callables %}{{ column.php_type.enum_class }}::from(...){% else %}[{{ column.php_type.enum_class }}::class, 'from']{% endif %}, explode(',', $row['{{ column.name }}']))){% elif column.php_type.is_set %}($row['{{ column.name }}'] === '' ? [] : explode(',', $row['{{ column.name }}'])){% else %}$row['{{ column.name }}']{% endif %}
+{%- endmacro row_value %}
+
+{% macro hydrate(target, class, columns, indent, php) -%}
+{{ indent }}${{ target }} = new {{ class }}();
+{%- for column in columns %}
+{{ indent }}${{ target }}->{{ column.name }} = {{ self::row_value(column=column, php=php) }};
+{%- endfor %}
+{%- endmacro hydrate %}
\ No newline at end of file
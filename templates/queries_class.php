@@ -8,17 +8,27 @@
  *
  * @generated
  */
+{% if namespace is defined and namespace is string and namespace != "" %}
 
-class Queries
+namespace {{ namespace }};
+
+{% endif %}
+{%- for use in uses %}
+use {{ use }};
+{%- endfor %}
+{% if uses %}
+{% endif %}class {{ class_name }}
 {
-    private \PDO $pdo;
+    /**
+     * @var {{ pdo }}
+     */
+    private {% if php.typed_properties %}{{ pdo }} {% endif %}$pdo;
 
-    public function __construct(\PDO $pdo)
+    public function __construct({{ pdo }} $pdo)
     {
         $this->pdo = $pdo;
-}
-
-{% for query in queries %}
-{{ query }}
-{% endfor %}
+    }
+{%- for query in queries %}
+{% include "query_function.php" %}
+{%- endfor %}
 }
\ No newline at end of file
@@ -1,48 +1,72 @@
-<?php
-/**
-* {{ query.description }}
-{% for param in query.params %}
-* @param {{ param.php_type.docblock_type }} ${{ param.name }}
-{% endfor %}
-* @return {{ query.return_type }}
-*/
-public function {{ query.name }}({% for param in query.params %}{{ param.php_type.type_hint }} ${{ param.name }}{% if not loop.last %}, {% endif %}{% endfor %}): {{ query.return_type_hint }}
-{
-    $stmt = $this->pdo->prepare("{{ query.sql }}");
-    $stmt->execute([{% for param in query.params %}${{ param.name }}{% if not loop.last %}, {% endif %}{% endfor %}]);
-
-    {% if query.returns == "one" %}
-    $row = $stmt->fetch(\PDO::FETCH_ASSOC);
-
-    if (!$row) {
-        return null;
-    }
-
-    $result = new {{ query.entity_class }}();
-
-    {% for column in query.result_columns %}
-    $result->{{ column.name }} = {% if column.php_type.php_type.simple_type == "int" %}(int){% endif %}{% if column.php_type.php_type.simple_type == "float" %}(float){% endif %}$row['{{ column.name }}'];
-    {% endfor %}
-
-    return $result;
-
-    {% elseif query.returns == "many" %}
-    $rows = $stmt->fetchAll(\PDO::FETCH_ASSOC);
-    $result = [];
-
-    foreach ($rows as $row) {
-        $item = new {{ query.entity_class }}();
-
-        {% for column in query.result_columns %}
-        $item->{{ column.name }} = {% if column.php_type.php_type.simple_type == "int" %}(int){% endif %}{% if column.php_type.php_type.simple_type == "float" %}(float){% endif %}$row['{{ column.name }}'];
-        {% endfor %}
-
-        $result[] = $item;
-    }
-
-    return $result;
-    {% elseif query.returns == "affected" %}
-
-    return $stmt->rowCount();
-    {% endif %}
-}
\ No newline at end of file
+
+    /**
+     * {{ query.name }} :{{ query.command }}
+     *
+{%- if query.is_batch %}
+     * @param list<{{ query.batch_shape }}> $batch
+{%- else %}
+{%- for param in query.params %}
+     * @param {{ param.php_type.docblock_type }} ${{ param.name }}
+{%- endfor %}
+{%- endif %}
+     * @return {{ query.return_type }}
+     */
+    public function {{ query.method_name }}({% if query.is_batch %}array $batch{% else %}{% for param in query.params %}{% if param.php_type.type_hint %}{{ param.php_type.type_hint }} {% endif %}${{ param.name }}{% if param.default_value %} = {{ param.default_value }}{% endif %}{% if not loop.last %}, {% endif %}{% endfor %}{% endif %}): {{ query.return_type_hint }}
+    {
+        $stmt = $this->pdo->prepare('{{ query.sql }}');
+{%- if query.command == "batchexec" %}
+
+        foreach ($batch as $params) {
+{%- for argument in query.batch_arguments %}
+            $stmt->bindValue({{ loop.index }}, {{ argument.value }}, {{ pdo }}::{{ argument.pdo_type }});
+{%- endfor %}
+            $stmt->execute();
+        }
+{%- elif query.command == "batchone" %}
+        $result = [];
+
+        foreach ($batch as $params) {
+{%- for argument in query.batch_arguments %}
+            $stmt->bindValue({{ loop.index }}, {{ argument.value }}, {{ pdo }}::{{ argument.pdo_type }});
+{%- endfor %}
+            $stmt->execute();
+            $row = $stmt->fetch({{ pdo }}::FETCH_ASSOC);
+            $stmt->closeCursor();
+
+            $result[] = $row === false ? null : {{ query.entity_class }}::fromRow($row);
+        }
+
+        return $result;
+{%- else %}
+{%- for argument in query.arguments %}
+        $stmt->bindValue({{ loop.index }}, {{ argument.value }}, {{ pdo }}::{{ argument.pdo_type }});
+{%- endfor %}
+        $stmt->execute();
+{%- if query.command == "one" %}
+        $row = $stmt->fetch({{ pdo }}::FETCH_ASSOC);
+
+        if ($row === false) {
+            return null;
+        }
+
+        return {{ query.entity_class }}::fromRow($row);
+{%- elif query.command == "many" %}
+        $result = [];
+
+        while (($row = $stmt->fetch({{ pdo }}::FETCH_ASSOC)) !== false) {
+            $result[] = {{ query.entity_class }}::fromRow($row);
+        }
+
+        return $result;
+{%- elif query.command == "execrows" %}
+
+        return $stmt->rowCount();
+{%- elif query.command == "execlastid" %}
+
+        return (int) $this->pdo->lastInsertId();
+{%- elif query.command == "execresult" %}
+
+        return $stmt;
+{%- endif %}
+{%- endif %}
+    }
\ No newline at end of file
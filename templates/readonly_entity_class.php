@@ -0,0 +1,71 @@
+{%- import "macros.php" as macros %}<?php
+/**
+ * This file is auto-generated by BDE (Boring Database Engine) v0.1.0.
+ * Do not edit this file directly.
+ *
+ * To regenerate this file, run:
+ * bde generate
+ *
+ * @generated
+ */
+{% if namespace is defined and namespace is string and namespace != "" %}
+
+namespace {{ namespace }};
+
+{% endif %}
+{%- for use in uses %}
+use {{ use }};
+{%- endfor %}
+{% if uses %}
+{% endif %}final {% if php.readonly_classes %}readonly {% endif %}class {{ class_name }}
+{
+    {%- if with_defaults %}{% for column in columns %}{% if column.default_value %}
+    public const {% if php.typed_class_constants %}{{ column.php_type.simple_type }} {% endif %}DEFAULT_{{ column.name | upper }} = {{ column.default_value }};
+    {%- endif %}{% endfor %}{% endif %}
+
+    /**
+    {%- for column in parameters %}
+     * @param {{ column.php_type.docblock_type }} ${{ column.name }}
+    {%- endfor %}
+     */
+    public function __construct(
+    {%- for column in parameters %}
+        public {% if not php.readonly_classes %}readonly {% endif %}{{ column.php_type.type_hint }} ${{ column.name }}{% if with_defaults and column.default_value %} = self::DEFAULT_{{ column.name | upper }}{% elif column.php_type.is_nullable %} = null{% endif %},
+    {%- endfor %}
+    ) {
+    }
+
+    /**
+     * @param array<string, mixed> $row
+     */
+    public static function fromRow(array $row): self
+    {
+        return new self(
+        {%- for column in columns %}
+            {{ column.name }}: {{ macros::row_value(column=column, php=php) }},
+        {%- endfor %}
+        );
+    }
+
+    /**
+     * @return array<string, mixed>
+     */
+    public function toArray(): array
+    {
+        return [
+        {%- for column in columns %}
+            '{{ column.name }}' => {{ column.database_value }},
+        {%- endfor %}
+        ];
+    }
+{%- for column in columns %}
+
+    /**
+     * @param {{ column.php_type.docblock_type }} ${{ column.name }}
+     */
+    public function {{ column.wither }}({{ column.php_type.type_hint }} ${{ column.name }}): self
+    {
+        return new self(...['{{ column.name }}' => ${{ column.name }}] + get_object_vars($this));
+    }
+{%- endfor %}
+}
\ No newline at end of file
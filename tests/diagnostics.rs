@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use bde::analyzer::{process_query_file, process_sql_file};
+    use bde::diagnostics::{Diagnostic, Diagnostics};
+    use std::fs;
+
+    fn engine_data() -> bde::analyzer::EngineData {
+        let content = fs::read_to_string("examples/schemas/schema.sql").unwrap();
+        process_sql_file(content).unwrap()
+    }
+
+    #[test]
+    fn test_render_points_at_the_source() {
+        let content = "-- name: GetUser :one\nSELECT * FROM users WHERE phone = ?;";
+        let diagnostic = Diagnostic::at("unknown column phone", 2, 27, 5)
+            .in_source("queries/users.sql", content);
+
+        assert_eq!(
+            diagnostic.render(false),
+            "error: unknown column phone
+ --> queries/users.sql:2:27
+  |
+2 | SELECT * FROM users WHERE phone = ?;
+  |                           ^^^^^"
+        );
+    }
+
+    #[test]
+    fn test_query_errors_are_collected() {
+        let content = "-- name: GetUserPhone :one
+SELECT * FROM users WHERE phone = ?;
+
+-- name: GetUser :one
+SELECT * FROM users WHERE id = ?;
+
+-- name: RenameUser :exec
+UPDATE users SET nickname = ? WHERE id = 'abc';";
+        let error = process_query_file(content.to_string(), &engine_data())
+            .err()
+            .unwrap();
+        let diagnostics = error.downcast::<Diagnostics>().unwrap();
+
+        let located: Vec<(usize, usize)> = diagnostics
+            .items
+            .iter()
+            .map(|diagnostic| (diagnostic.line, diagnostic.column))
+            .collect();
+        assert_eq!(located, vec![(2, 27), (8, 18), (8, 42)]);
+        assert!(diagnostics.items[0].message.contains("GetUserPhone"));
+        assert!(diagnostics.items[2].message.contains("type mismatch"));
+    }
+
+    #[test]
+    fn test_schema_errors_are_located() {
+        let content = "CREATE TABLE users (id INT);\n\nALTER TABLE accounts ADD COLUMN name TEXT;";
+        let error = process_sql_file(content.to_string()).err().unwrap();
+        let diagnostics = error.downcast::<Diagnostics>().unwrap();
+
+        assert_eq!(diagnostics.items.len(), 1);
+        assert_eq!(diagnostics.items[0].line, 3);
+        assert!(diagnostics.items[0].message.contains("accounts"));
+    }
+}
@@ -0,0 +1,445 @@
+#[cfg(test)]
+mod tests {
+    use bde::analyzer::PhpType::{Int, Mixed, String};
+    use bde::analyzer::{
+        process_query_file, process_sql_file, process_sql_file_for_engine,
+        process_sql_file_with_types,
+    };
+    use bde::configuration::{DecimalStrategy, Engine, TypeSettings};
+    use bde::parser::QueryCommand;
+    use std::fs;
+
+    fn engine_data() -> bde::analyzer::EngineData {
+        let content = fs::read_to_string("examples/schemas/schema.sql").unwrap();
+        process_sql_file(content).unwrap()
+    }
+
+    #[test]
+    fn test_query_analyzer() {
+        let content = fs::read_to_string("examples/queries/users.sql").unwrap();
+        let queries = process_query_file(content, &engine_data()).unwrap();
+        assert_eq!(queries.len(), 5);
+
+        let get_user = &queries[0];
+        assert_eq!(get_user.name, "GetUserByID");
+        assert_eq!(get_user.command, QueryCommand::One);
+        assert_eq!(get_user.sql, "SELECT * FROM users WHERE id = ?");
+        assert_eq!(get_user.result_table.as_deref(), Some("users"));
+        assert_eq!(get_user.result_columns.len(), 5);
+        assert_eq!(get_user.bindings, vec!["id"]);
+
+        let delete_user = &queries[1];
+        assert_eq!(delete_user.command, QueryCommand::Exec);
+        assert!(delete_user.result_columns.is_empty());
+    }
+
+    #[test]
+    fn test_repeated_placeholders_share_a_parameter() {
+        let content = "-- name: FindUsers :many
+SELECT * FROM users WHERE username = :term OR email = :term OR id = ?;";
+        let queries = process_query_file(content.to_string(), &engine_data()).unwrap();
+
+        let query = &queries[0];
+        assert_eq!(query.params.len(), 2);
+        assert_eq!(query.bindings, vec!["term", "term", "id"]);
+        assert_eq!(
+            query.sql,
+            "SELECT * FROM users WHERE username = ? OR email = ? OR id = ?"
+        );
+    }
+
+    #[test]
+    fn test_placeholders_the_inference_skips_do_not_shift_the_others() {
+        let content = "-- name: FindBySubstring :many
+SELECT * FROM users WHERE SUBSTRING(username, ?, 2) = 'a' AND id = ?;
+
+-- name: FindRecent :many
+SELECT * FROM users WHERE created_at > NOW() - INTERVAL ? DAY AND username = ?;";
+        let queries = process_query_file(content.to_string(), &engine_data()).unwrap();
+
+        let types = |index: usize| -> Vec<_> {
+            queries[index]
+                .params
+                .iter()
+                .map(|param| (param.name.clone(), param.php_type.php_type.clone()))
+                .collect()
+        };
+        assert_eq!(
+            types(0),
+            vec![("arg1".to_string(), Mixed), ("id".to_string(), Int)]
+        );
+        assert_eq!(queries[0].bindings, vec!["arg1", "id"]);
+        assert_eq!(
+            types(1),
+            vec![
+                ("arg1".to_string(), Mixed),
+                ("username".to_string(), String)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_param_type_inference() {
+        let content = "-- name: GetUserByID :one
+SELECT * FROM users WHERE id = :id;
+
+-- name: UpdateUserName :exec
+UPDATE users SET name = ? WHERE email = ?;
+
+-- name: CreateOauth :exec
+INSERT INTO oauth (user_id, provider, provider_user_id) VALUES (?, ?, ?);
+
+-- name: ListUsers :many
+SELECT * FROM users u WHERE u.username LIKE ? AND u.id IN (?, ?) LIMIT ? OFFSET ?;";
+        let queries = process_query_file(content.to_string(), &engine_data()).unwrap();
+
+        let id = &queries[0].params[0];
+        assert_eq!(id.php_type.php_type, Int);
+        assert!(!id.php_type.is_nullable);
+
+        let update = &queries[1].params;
+        assert_eq!(update[0].name, "name");
+        assert_eq!(update[0].php_type.type_hint, "?string");
+        assert_eq!(update[1].name, "email");
+        assert_eq!(update[1].php_type.type_hint, "string");
+
+        let insert = &queries[2].params;
+        assert_eq!(insert[0].name, "user_id");
+        assert_eq!(insert[0].php_type.type_hint, "?int");
+        assert_eq!(insert[1].php_type.php_type, String);
+
+        let list = &queries[3].params;
+        assert_eq!(list[0].name, "username");
+        assert_eq!(list[0].php_type.php_type, String);
+        assert_eq!(list[1].name, "id");
+        assert_eq!(list[2].name, "id_2");
+        assert_eq!(list[3].name, "limit");
+        assert_eq!(list[3].php_type.php_type, Int);
+        assert_ne!(list[3].php_type.php_type, Mixed);
+        assert_eq!(list[4].name, "offset");
+        assert_eq!(list[4].php_type.php_type, Int);
+    }
+
+    #[test]
+    fn test_result_column_resolution() {
+        let content = "-- name: GetUserEmails :many
+SELECT u.id, u.email AS address, CONCAT(u.username, '@') AS handle FROM users u;
+
+-- name: ListOauth :many
+SELECT o.* FROM oauth o;
+
+-- name: CountUsers :one
+SELECT COUNT(*) AS count, SUM(id) AS total, COALESCE(name, username) AS display, CAST(id AS CHAR) AS code FROM users;";
+        let queries = process_query_file(content.to_string(), &engine_data()).unwrap();
+
+        let emails = &queries[0];
+        assert_eq!(emails.result_table, None);
+        let names: Vec<&str> = emails
+            .result_columns
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["id", "address", "handle"]);
+        assert_eq!(emails.result_columns[0].php_type.type_hint, "int");
+        assert_eq!(emails.result_columns[2].php_type.type_hint, "string");
+
+        assert_eq!(queries[1].result_table.as_deref(), Some("oauth"));
+
+        let count = &queries[2].result_columns;
+        assert_eq!(count[0].name, "count");
+        assert_eq!(count[0].php_type.type_hint, "int");
+        assert_eq!(count[1].php_type.type_hint, "?int");
+        assert_eq!(count[2].php_type.type_hint, "string");
+        assert_eq!(count[3].php_type.type_hint, "string");
+    }
+
+    #[test]
+    fn test_aggregates_of_decimals_keep_the_decimal_strategy() {
+        let schema = "CREATE TABLE products (id INT PRIMARY KEY, price DECIMAL(10, 2) NOT NULL);";
+        let content = "-- name: Totals :one
+SELECT SUM(price) AS total, AVG(price) AS average, SUM(id) AS ids, AVG(id) AS mean FROM products;";
+        let hints = |types: TypeSettings| -> Vec<std::string::String> {
+            let engine_data =
+                process_sql_file_with_types(schema.to_string(), Engine::MySql, types).unwrap();
+            process_query_file(content.to_string(), &engine_data).unwrap()[0]
+                .result_columns
+                .iter()
+                .map(|column| column.php_type.type_hint.clone())
+                .collect()
+        };
+
+        assert_eq!(
+            hints(TypeSettings::default()),
+            vec!["?string", "?string", "?int", "?float"]
+        );
+        let types = TypeSettings {
+            decimal: DecimalStrategy::BigDecimal,
+            ..Default::default()
+        };
+        assert_eq!(
+            hints(types),
+            vec![
+                "?\\Brick\\Math\\BigDecimal",
+                "?\\Brick\\Math\\BigDecimal",
+                "?int",
+                "?float"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unaliased_expression_is_an_error() {
+        let content = "-- name: CountUsers :one
+SELECT COUNT(*) FROM users;";
+        let error = process_query_file(content.to_string(), &engine_data()).err();
+        assert!(error.unwrap().to_string().contains("AS <name>"));
+    }
+
+    #[test]
+    fn test_unknown_result_column_is_an_error() {
+        let content = "-- name: GetUserPhone :one
+SELECT phone FROM users;";
+        let error = process_query_file(content.to_string(), &engine_data())
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(error.contains("GetUserPhone"));
+        assert!(error.contains("phone"));
+    }
+
+    #[test]
+    fn test_duplicate_query_name_is_an_error() {
+        let content = "-- name: GetUser :one
+SELECT * FROM users WHERE id = :id;
+
+-- name: getUser :one
+SELECT * FROM users WHERE username = :username;";
+        let error = process_query_file(content.to_string(), &engine_data())
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(error.contains("query getUser is already defined at line 1"));
+    }
+
+    #[test]
+    fn test_outer_joins_make_columns_nullable() {
+        let content = "-- name: LeftJoin :many
+SELECT u.*, o.provider FROM users u LEFT JOIN oauth o ON o.user_id = u.id;
+
+-- name: RightJoin :many
+SELECT u.username, o.provider FROM users u RIGHT JOIN oauth o ON o.user_id = u.id;
+
+-- name: FullJoin :many
+SELECT u.username, o.provider FROM users u FULL OUTER JOIN oauth o ON o.user_id = u.id;
+
+-- name: InnerJoin :many
+SELECT u.username, o.provider FROM users u INNER JOIN oauth o ON o.user_id = u.id CROSS JOIN users x;";
+        let queries = process_query_file(content.to_string(), &engine_data()).unwrap();
+        let hints = |index: usize| -> Vec<std::string::String> {
+            queries[index]
+                .result_columns
+                .iter()
+                .map(|column| column.php_type.type_hint.clone())
+                .collect()
+        };
+
+        assert_eq!(
+            hints(0),
+            vec![
+                "int",
+                "string",
+                "string",
+                "\\DateTimeImmutable",
+                "?string",
+                "?string"
+            ]
+        );
+        assert_eq!(queries[0].result_table, None);
+        assert_eq!(hints(1), vec!["?string", "string"]);
+        assert_eq!(hints(2), vec!["?string", "?string"]);
+        assert_eq!(hints(3), vec!["string", "string"]);
+    }
+
+    #[test]
+    fn test_numbered_placeholders_are_ordered_by_number() {
+        let schema = fs::read_to_string("examples/schemas/schema.sql").unwrap();
+        let engine_data = process_sql_file_for_engine(schema, Engine::PostgreSql).unwrap();
+        let content = "-- name: FindUsers :many
+SELECT * FROM users WHERE username = $2 AND id = $1 AND email = $2;";
+        let queries = process_query_file(content.to_string(), &engine_data).unwrap();
+
+        let names: Vec<&str> = queries[0]
+            .params
+            .iter()
+            .map(|param| param.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["id", "username"]);
+        assert_eq!(queries[0].bindings, vec!["username", "id", "username"]);
+    }
+
+    #[test]
+    fn test_enum_literals_are_checked() {
+        let engine_data = process_sql_file(
+            "CREATE TABLE tasks (id INT, state ENUM('todo', 'done') NOT NULL);".to_string(),
+        )
+        .unwrap();
+        let content = "-- name: ListTasks :many
+SELECT * FROM tasks WHERE state = 'doing' OR state = :state;";
+        let error = process_query_file(content.to_string(), &engine_data)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(
+            error.contains("'doing' is not a value of column state, expected one of todo, done"),
+            "{}",
+            error
+        );
+
+        let engine_data = process_sql_file(
+            "CREATE TABLE grants (id INT, permissions SET('read', 'write'));".to_string(),
+        )
+        .unwrap();
+        let content = "-- name: ListReaders :many
+SELECT * FROM grants WHERE permissions = 'read,write' OR permissions = 'read,delete';";
+        let error = process_query_file(content.to_string(), &engine_data)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(
+            error.contains("'delete' is not a value of column permissions"),
+            "{}",
+            error
+        );
+        assert!(!error.contains("'read,write'"), "{}", error);
+    }
+
+    #[test]
+    fn test_generated_and_expression_defaults_make_insert_parameters_optional() {
+        let engine_data = process_sql_file(
+            "CREATE TABLE posts (
+    id INT AUTO_INCREMENT PRIMARY KEY,
+    title TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    published_at TIMESTAMP NULL DEFAULT CURRENT_TIMESTAMP
+);"
+            .to_string(),
+        )
+        .unwrap();
+        let content = "-- name: CreatePost :execlastid
+INSERT INTO posts (published_at, title, id, created_at) VALUES (:published_at, :title, :id, :created_at);";
+        let queries = process_query_file(content.to_string(), &engine_data).unwrap();
+        let params: Vec<(&str, &str, Option<&str>)> = queries[0]
+            .params
+            .iter()
+            .map(|param| {
+                (
+                    param.name.as_str(),
+                    param.php_type.type_hint.as_str(),
+                    param.default_value.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            params,
+            vec![
+                ("published_at", "?\\DateTimeImmutable", None),
+                ("title", "string", None),
+                ("id", "?int", Some("null")),
+                ("created_at", "?\\DateTimeImmutable", Some("null"))
+            ]
+        );
+        // MySQL numbers a NULL id itself, the NOT NULL timestamp falls back to its default and
+        // the nullable one stores NULL
+        assert_eq!(
+            queries[0].sql,
+            "INSERT INTO posts (published_at, title, id, created_at) VALUES (?, ?, ?, COALESCE(?, CURRENT_TIMESTAMP))"
+        );
+
+        let engine_data = process_sql_file_for_engine(
+            "CREATE TABLE posts (id SERIAL PRIMARY KEY, title TEXT NOT NULL);".to_string(),
+            Engine::PostgreSql,
+        )
+        .unwrap();
+        let content = "-- name: CreatePost :exec
+INSERT INTO posts (title, id) VALUES (:title, :id);";
+        let queries = process_query_file(content.to_string(), &engine_data).unwrap();
+        assert_eq!(queries[0].params[1].default_value.as_deref(), Some("null"));
+        assert_eq!(
+            queries[0].sql,
+            "INSERT INTO posts (title, id) VALUES (?, COALESCE(?, nextval(pg_get_serial_sequence('posts', 'id'))))"
+        );
+    }
+
+    #[test]
+    fn test_question_marks_in_literals_are_not_placeholders() {
+        let engine_data = process_sql_file_for_engine(
+            "CREATE TABLE posts (id SERIAL PRIMARY KEY, title TEXT NOT NULL);".to_string(),
+            Engine::PostgreSql,
+        )
+        .unwrap();
+        let content = "-- name: GetPost :one
+SELECT $$why?$$ AS x, title FROM posts WHERE id = $1;
+
+-- name: CreatePost :exec
+INSERT INTO posts (title, id) VALUES (E'it\\'s?', $1);";
+        let queries = process_query_file(content.to_string(), &engine_data).unwrap();
+        assert_eq!(
+            queries[0].sql,
+            "SELECT $$why?$$ AS x, title FROM posts WHERE id = ?"
+        );
+        assert_eq!(
+            queries[1].sql,
+            "INSERT INTO posts (title, id) VALUES (E'it\\'s?', COALESCE(?, nextval(pg_get_serial_sequence('posts', 'id'))))"
+        );
+    }
+
+    #[test]
+    fn test_common_table_expressions_are_tables_of_the_query() {
+        let content = "-- name: ListActive :many
+WITH active (user_id, login) AS (
+    SELECT id, username FROM users WHERE email LIKE :domain
+), named AS (
+    SELECT a.user_id, u.name FROM active a JOIN users u ON u.id = a.user_id
+)
+SELECT a.login, n.name FROM active a JOIN named n ON n.user_id = a.user_id WHERE a.user_id > :min_id;";
+        let queries = process_query_file(content.to_string(), &engine_data()).unwrap();
+        let query = &queries[0];
+        let params: Vec<(&str, &str)> = query
+            .params
+            .iter()
+            .map(|param| (param.name.as_str(), param.php_type.type_hint.as_str()))
+            .collect();
+        assert_eq!(params, vec![("domain", "string"), ("min_id", "int")]);
+        let columns: Vec<(&str, &str)> = query
+            .result_columns
+            .iter()
+            .map(|column| (column.name.as_str(), column.php_type.type_hint.as_str()))
+            .collect();
+        assert_eq!(columns, vec![("login", "string"), ("name", "?string")]);
+        assert_eq!(query.result_table, None);
+
+        let content = "-- name: GetUser :one
+WITH users AS (SELECT * FROM users WHERE id = :id) SELECT * FROM users;";
+        let queries = process_query_file(content.to_string(), &engine_data()).unwrap();
+        assert_eq!(queries[0].result_table.as_deref(), Some("users"));
+
+        // A column list names expressions, a recursive query reads its own first SELECT
+        let content = "-- name: Numbers :many
+WITH RECURSIVE n (x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n WHERE x < :max) SELECT x FROM n;";
+        let queries = process_query_file(content.to_string(), &engine_data()).unwrap();
+        assert_eq!(queries[0].result_columns[0].php_type.type_hint, "int");
+        assert_eq!(queries[0].params[0].php_type.type_hint, "int");
+
+        let content = "-- name: ListUsers :many
+SELECT * FROM users WHERE id IN (WITH ids AS (SELECT id FROM users) SELECT id FROM ids);";
+        let error = process_query_file(content.to_string(), &engine_data())
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(
+            error.contains("WITH is only supported at the start of a query"),
+            "{}",
+            error
+        );
+    }
+}
@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
-    use bde::parser::{QuerySqlFileParser, debug_statement_structure};
+    use bde::configuration::Engine;
+    use bde::parser::{QueryCommand, QuerySqlFileParser, debug_statement_structure};
 
     #[test]
     fn test_queries_sql_file_parser() {
@@ -15,7 +16,7 @@ FROM users
 WHERE id = 1;";
 
         let parser = QuerySqlFileParser::default();
-        let queries = parser.divide_content_into_queries(&content).unwrap();
+        let queries = parser.divide_content_into_queries(content).unwrap();
         let statements = parser.parse_sql(&queries).unwrap();
         for statemnt in statements {
             for dmg in statemnt {
@@ -23,4 +24,94 @@ WHERE id = 1;";
             }
         }
     }
+
+    #[test]
+    fn test_query_commands() {
+        let content = "-- name: CountUsers :execrows
+DELETE FROM users;
+
+-- name: CreateUser :execlastid
+INSERT INTO users (username) VALUES (?);
+
+-- name: CreateUsers :batchexec
+INSERT INTO users (username) VALUES (?);";
+
+        let parser = QuerySqlFileParser::default();
+        let queries = parser.parse_content(content).unwrap();
+        let commands: Vec<QueryCommand> = queries.iter().map(|query| query.command).collect();
+        assert_eq!(
+            commands,
+            vec![
+                QueryCommand::ExecRows,
+                QueryCommand::ExecLastId,
+                QueryCommand::BatchExec
+            ]
+        );
+        assert_eq!(queries[1].line, 4);
+        assert!(queries[2].command.is_batch());
+    }
+
+    #[test]
+    fn test_unknown_query_command() {
+        let content = "-- name: GetUser :one
+SELECT * FROM users;
+
+-- name: DeleteUser :affected
+DELETE FROM users;";
+
+        let parser = QuerySqlFileParser::default();
+        let error = parser.parse_content(content).err().unwrap().to_string();
+        assert!(error.contains("--> 4:"), "{}", error);
+        assert!(error.contains(":affected"));
+    }
+
+    #[test]
+    fn test_postgres_placeholders() {
+        let content = "-- name: FindUsers :many
+SELECT * FROM users WHERE id = $2 AND (username = $1 OR email = $1) AND data ? 'admin';";
+
+        let parser = QuerySqlFileParser::new(Engine::PostgreSql);
+        let queries = parser.parse_content(content).unwrap();
+        assert_eq!(queries[0].placeholders, vec!["$2", "$1", "$1"]);
+        assert_eq!(
+            queries[0].sql,
+            "SELECT * FROM users WHERE id = ? AND (username = ? OR email = ?) AND data ?? 'admin'"
+        );
+        assert_eq!(
+            queries[0].numbered_sql,
+            "SELECT * FROM users WHERE id = $1 AND (username = $2 OR email = $3) AND data ? 'admin'"
+        );
+    }
+
+    #[test]
+    fn test_placeholder_style_is_checked_per_engine() {
+        let content = "-- name: GetUser :one
+SELECT * FROM users WHERE id = $1;";
+
+        let parser = QuerySqlFileParser::new(Engine::MySql);
+        let error = parser.parse_content(content).err().unwrap().to_string();
+        assert!(error.contains("$1 is not supported by mysql"), "{}", error);
+        assert!(error.contains("--> 2:32"), "{}", error);
+
+        let parser = QuerySqlFileParser::new(Engine::Sqlite);
+        let queries = parser
+            .parse_content("-- name: GetUser :one\nSELECT * FROM users WHERE id = ?1;")
+            .unwrap();
+        assert_eq!(queries[0].placeholders, vec!["?1"]);
+    }
+
+    #[test]
+    fn test_literals_are_copied_as_written() {
+        let content = "-- name: FindUsers :many
+SELECT * FROM users WHERE username = 'O''Brien' OR username = 'a\\'b' -- a comment
+  AND   id = :id;";
+
+        let parser = QuerySqlFileParser::new(Engine::MySql);
+        let queries = parser.parse_content(content).unwrap();
+        assert_eq!(
+            queries[0].sql,
+            "SELECT * FROM users WHERE username = 'O''Brien' OR username = 'a\\'b' AND id = ?"
+        );
+        assert_eq!(queries[0].placeholders, vec![":id"]);
+    }
 }
@@ -1,9 +1,16 @@
 #[cfg(test)]
 mod tests {
-    use bde::analyzer::PhpType::Int;
-    use bde::analyzer::process_sql_file;
-    use bde::configuration::get_configuration;
-    use bde::parser::SqlFileParser;
+    use bde::analyzer::PhpType::{Bool, Enum, Int, Mixed, String};
+    use bde::analyzer::{
+        Generated, PhpType, process_sql_file, process_sql_file_for_engine,
+        process_sql_file_with_types,
+    };
+    use bde::configuration::{
+        DateTimeStrategy, DecimalStrategy, Engine, PhpVersion, Settings, TypeSettings,
+        UnsignedBigIntStrategy, get_configuration,
+    };
+    use bde::parser::{SqlFileParser, collect_sql_files, natural_cmp};
+    use sqlparser::ast::ReferentialAction;
     use std::fs;
     use std::ops::Add;
     use std::path::Path;
@@ -11,16 +18,18 @@ mod tests {
     #[test]
     fn test_schema_analyzer() {
         // Check if configuration file exists
-        assert_eq!(Path::new("bde.yaml").exists(), true);
+        assert!(Path::new("bde.yaml").exists());
 
         // Try to parse the configuration
         let configuration = get_configuration().expect("Failed to parse configuration");
 
-        let p = configuration.sql.schemas.add("/schema.sql");
+        let p = configuration.sql[0].schemas[0].clone().add("/schema.sql");
         let path = Path::new(&p);
-        let content = fs::read_to_string(&path).unwrap();
-        let tables = process_sql_file(content).unwrap().tables;
-        assert!(0 < tables.len());
+        let content = fs::read_to_string(path).unwrap();
+        let tables = process_sql_file_for_engine(content, configuration.sql[0].engine)
+            .unwrap()
+            .tables;
+        assert!(!tables.is_empty());
 
         // Check number of columns for user table
         let user_table = tables.first().unwrap();
@@ -30,12 +39,12 @@ mod tests {
     #[test]
     fn test_sql_file_parser() {
         // Check if configuration file exists
-        assert_eq!(Path::new("bde.yaml").exists(), true);
+        assert!(Path::new("bde.yaml").exists());
 
         // Try to parse the configuration
         let configuration = get_configuration().expect("Failed to parse configuration");
 
-        let p = configuration.sql.schemas.add("/schema.sql");
+        let p = configuration.sql[0].schemas[0].clone().add("/schema.sql");
         let path = Path::new(&p);
 
         let parser = SqlFileParser::default();
@@ -47,20 +56,829 @@ mod tests {
     #[test]
     fn test_php_type_conversion() {
         // Check if configuration file exists
-        assert_eq!(Path::new("bde.yaml").exists(), true);
+        assert!(Path::new("bde.yaml").exists());
 
         // Try to parse the configuration
         let configuration = get_configuration().expect("Failed to parse configuration");
 
-        let p = configuration.sql.schemas.add("/schema.sql");
+        let p = configuration.sql[0].schemas[0].clone().add("/schema.sql");
         let path = Path::new(&p);
-        let content = fs::read_to_string(&path).unwrap();
-        let tables = process_sql_file(content).unwrap().tables;
-        assert!(0 < tables.len());
+        let content = fs::read_to_string(path).unwrap();
+        let tables = process_sql_file_for_engine(content, configuration.sql[0].engine)
+            .unwrap()
+            .tables;
+        assert!(!tables.is_empty());
 
         // Check number of columns for user table
         let user_table = tables.first().unwrap();
         let id = user_table.columns.first().unwrap();
         assert_eq!(id.data_type.php_type.php_type, Int);
     }
+
+    #[test]
+    fn test_php_types_depend_on_engine() {
+        let content =
+            "CREATE TABLE events (id BIGSERIAL, active TINYINT(1), year YEAR, ip INET, body TEXT);";
+        let types = |engine| {
+            let tables = process_sql_file_for_engine(content.to_string(), engine)
+                .unwrap()
+                .tables;
+            tables[0]
+                .columns
+                .iter()
+                .map(|column| column.data_type.php_type.php_type.clone())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(types(Engine::MySql), vec![Mixed, Bool, Int, Mixed, String]);
+        assert_eq!(
+            types(Engine::PostgreSql),
+            vec![Int, Int, Mixed, String, String]
+        );
+    }
+
+    #[test]
+    fn test_decimal_and_unsigned_bigint_strategies() {
+        let content = "CREATE TABLE accounts (
+    id INT UNSIGNED,
+    balance DECIMAL(20, 4) NOT NULL DEFAULT 0.5,
+    views BIGINT UNSIGNED,
+    rate DOUBLE
+);";
+        let columns = |types| {
+            let tables = process_sql_file_with_types(content.to_string(), Engine::MySql, types)
+                .unwrap()
+                .tables;
+            tables[0]
+                .columns
+                .iter()
+                .map(|column| column.data_type.php_type.php_type.clone())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            columns(TypeSettings::default()),
+            vec![Int, String, String, PhpType::Float]
+        );
+        let types = TypeSettings {
+            decimal: DecimalStrategy::BigDecimal,
+            unsigned_bigint: UnsignedBigIntStrategy::Int,
+            ..Default::default()
+        };
+        assert_eq!(
+            columns(types),
+            vec![
+                Int,
+                PhpType::BigDecimal("\\Brick\\Math\\BigDecimal".to_string()),
+                Int,
+                PhpType::Float
+            ]
+        );
+        let types = TypeSettings {
+            decimal: DecimalStrategy::Float,
+            ..Default::default()
+        };
+        assert_eq!(columns(types)[1], PhpType::Float);
+
+        // The exact digits of a default survive as a string
+        let tables = process_sql_file(content.to_string()).unwrap().tables;
+        let default = tables[0].columns[1].default.as_ref().unwrap();
+        assert_eq!(default.php_value.as_deref(), Some("'0.5'"));
+    }
+
+    #[test]
+    fn test_date_time_formats_follow_the_sql_type() {
+        let formats = |content: &str, engine| {
+            let tables = process_sql_file_for_engine(content.to_string(), engine)
+                .unwrap()
+                .tables;
+            tables[0]
+                .columns
+                .iter()
+                .map(|column| {
+                    let php_type = &column.data_type.php_type;
+                    (
+                        php_type.simple_type.clone(),
+                        php_type.date_format.clone(),
+                        php_type.date_format_exact,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let date_time = |format: &str, exact| {
+            (
+                "\\DateTimeImmutable".to_string(),
+                Some(format.to_string()),
+                exact,
+            )
+        };
+        let scalar = |php_type: &str| (php_type.to_string(), None, false);
+
+        assert_eq!(
+            formats(
+                "CREATE TABLE events (a DATE, b DATETIME, c DATETIME(6), d TIMESTAMP, e TIME, f YEAR);",
+                Engine::MySql
+            ),
+            vec![
+                date_time("Y-m-d", true),
+                date_time("Y-m-d H:i:s", true),
+                date_time("Y-m-d H:i:s.u", false),
+                date_time("Y-m-d H:i:s", true),
+                scalar("string"),
+                scalar("int"),
+            ]
+        );
+        assert_eq!(
+            formats(
+                "CREATE TABLE events (a TIMESTAMP, b TIMESTAMPTZ, c TIMESTAMP WITH TIME ZONE, d TIME WITH TIME ZONE);",
+                Engine::PostgreSql
+            ),
+            vec![
+                date_time("Y-m-d H:i:s.u", false),
+                date_time("Y-m-d H:i:s.uP", false),
+                date_time("Y-m-d H:i:s.uP", false),
+                scalar("string"),
+            ]
+        );
+
+        let types = TypeSettings {
+            date_time: DateTimeStrategy::CarbonImmutable,
+            ..Default::default()
+        };
+        let tables = process_sql_file_with_types(
+            "CREATE TABLE events (a DATE);".to_string(),
+            Engine::MySql,
+            types,
+        )
+        .unwrap()
+        .tables;
+        assert_eq!(
+            tables[0].columns[0].data_type.php_type.type_hint,
+            "?\\Carbon\\CarbonImmutable"
+        );
+    }
+
+    #[test]
+    fn test_sqlite_type_affinity() {
+        let content = "CREATE TABLE notes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    code INT UNIQUE,
+    title VARCHAR(255) NOT NULL,
+    weight DOUBLE,
+    price DECIMAL(10, 2),
+    done BOOLEAN,
+    created_at DATETIME,
+    data BLOB,
+    anything,
+    location POINT
+);";
+        let tables = process_sql_file_for_engine(content.to_string(), Engine::Sqlite)
+            .unwrap()
+            .tables;
+        let types: Vec<_> = tables[0]
+            .columns
+            .iter()
+            .map(|column| column.data_type.php_type.type_hint.clone())
+            .collect();
+
+        assert_eq!(
+            types,
+            vec![
+                "int",
+                "?int",
+                "string",
+                "?float",
+                "?string",
+                "?bool",
+                "?\\DateTimeImmutable",
+                "?string",
+                "mixed",
+                "?int"
+            ]
+        );
+        // POINT contains INT, so SQLite gives it integer affinity
+        assert_eq!(tables[0].columns[9].data_type.php_type.php_type, Int);
+    }
+
+    #[test]
+    fn test_sqlite_autoincrement_requires_rowid_alias() {
+        let content = "CREATE TABLE notes (id INT PRIMARY KEY AUTOINCREMENT);";
+        let error = process_sql_file_for_engine(content.to_string(), Engine::Sqlite)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(
+            error.contains("only allowed on an INTEGER PRIMARY KEY"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_alter_table_migrations() {
+        let content = "CREATE TABLE users (id INT NOT NULL, name VARCHAR(255), legacy TEXT);
+CREATE TABLE sessions (id INT);
+ALTER TABLE users DROP COLUMN legacy;
+ALTER TABLE users RENAME COLUMN name TO display_name;
+ALTER TABLE users ADD COLUMN email VARCHAR(255) NOT NULL AFTER id;
+ALTER TABLE users MODIFY display_name VARCHAR(100) NOT NULL;
+ALTER TABLE users CHANGE id user_id BIGINT NOT NULL;
+ALTER TABLE users ALTER COLUMN email DROP NOT NULL;
+ALTER TABLE users RENAME TO members;
+DROP TABLE sessions;";
+        let tables = process_sql_file(content.to_string()).unwrap().tables;
+        assert_eq!(tables.len(), 1);
+
+        let members = &tables[0];
+        assert_eq!(members.name, "members");
+        let columns: Vec<(&str, &str)> = members
+            .columns
+            .iter()
+            .map(|column| {
+                (
+                    column.name.as_str(),
+                    column.data_type.php_type.type_hint.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            columns,
+            vec![
+                ("user_id", "int"),
+                ("email", "?string"),
+                ("display_name", "string")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_alter_table_follows_quoting_keys_and_references() {
+        let content = "CREATE TABLE `users` (`id` INT PRIMARY KEY, `name` TEXT);
+ALTER TABLE users RENAME COLUMN `name` TO display_name;
+ALTER TABLE `users` MODIFY id BIGINT;";
+        let users = process_sql_file(content.to_string())
+            .unwrap()
+            .tables
+            .remove(0);
+        assert_eq!(users.columns[1].name, "display_name");
+        // The primary key is still there, so MODIFY leaves it NOT NULL
+        assert_eq!(users.columns[0].data_type.php_type.type_hint, "int");
+
+        let content = "CREATE TABLE a (id INT, PRIMARY KEY (id));
+ALTER TABLE a CHANGE id a_id BIGINT;";
+        let a = process_sql_file(content.to_string())
+            .unwrap()
+            .tables
+            .remove(0);
+        assert_eq!(a.primary_key, vec!["a_id"]);
+        assert_eq!(a.columns[0].data_type.php_type.type_hint, "int");
+
+        let content = "CREATE TABLE users (id INT PRIMARY KEY);
+CREATE TABLE posts (id INT PRIMARY KEY, author INT REFERENCES users (id));
+ALTER TABLE users RENAME COLUMN id TO user_id;
+ALTER TABLE users CHANGE user_id uid INT;";
+        let engine_data = process_sql_file(content.to_string()).unwrap();
+        let posts = engine_data.find_table("posts").unwrap();
+        assert_eq!(posts.foreign_keys[0].referenced_columns, vec!["uid"]);
+    }
+
+    #[test]
+    fn test_postgres_alter_column() {
+        let content = "CREATE TABLE users (id SERIAL PRIMARY KEY, age TEXT);
+ALTER TABLE users ALTER COLUMN age TYPE INT, ALTER COLUMN age SET NOT NULL;
+ALTER TABLE users DROP COLUMN IF EXISTS missing;";
+        let tables = process_sql_file_for_engine(content.to_string(), Engine::PostgreSql)
+            .unwrap()
+            .tables;
+        assert_eq!(tables[0].columns[1].data_type.php_type.type_hint, "int");
+
+        let error = process_sql_file(
+            "CREATE TABLE users (id INT);
+ALTER TABLE users DROP COLUMN missing;"
+                .to_string(),
+        )
+        .err()
+        .unwrap()
+        .to_string();
+        assert!(
+            error.contains("table users has no column missing"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_schema_files_load_in_migration_order() {
+        let dir = "target/test-output/schema_order";
+        let _ = fs::remove_dir_all(dir);
+        fs::create_dir_all(format!("{}/later", dir)).unwrap();
+        fs::write(
+            format!("{}/10_add_email.sql", dir),
+            "ALTER TABLE users ADD COLUMN email TEXT;",
+        )
+        .unwrap();
+        fs::write(
+            format!("{}/2_create_users.sql", dir),
+            "CREATE TABLE users (id INT);",
+        )
+        .unwrap();
+        fs::write(
+            format!("{}/later/11_add_name.sql", dir),
+            "ALTER TABLE users ADD COLUMN name TEXT;",
+        )
+        .unwrap();
+        fs::write(format!("{}/notes.md", dir), "not sql").unwrap();
+
+        let files = collect_sql_files(&[dir.to_string()]).unwrap();
+        let names: Vec<_> = files
+            .iter()
+            .map(|file| {
+                file.strip_prefix(dir)
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "2_create_users.sql",
+                "10_add_email.sql",
+                "later/11_add_name.sql"
+            ]
+        );
+
+        // A listed file comes first, the glob does not repeat it
+        let files = collect_sql_files(&[
+            format!("{}/10_add_email.sql", dir),
+            format!("{}/*.sql", dir),
+        ])
+        .unwrap();
+        assert_eq!(files.len(), 2);
+        assert!(files[0].ends_with("10_add_email.sql"));
+        assert!(files[1].ends_with("2_create_users.sql"));
+
+        let sql_files = SqlFileParser::default()
+            .parse_paths(&[dir.to_string()])
+            .unwrap();
+        assert_eq!(sql_files.len(), 3);
+        assert!(collect_sql_files(&[format!("{}/missing", dir)]).is_err());
+    }
+
+    #[test]
+    fn test_natural_order() {
+        let mut names = vec![
+            "10.sql",
+            "9.sql",
+            "0001_init.sql",
+            "002.sql",
+            "b.sql",
+            "a10.sql",
+            "a2.sql",
+        ];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(
+            names,
+            vec![
+                "0001_init.sql",
+                "002.sql",
+                "9.sql",
+                "10.sql",
+                "a2.sql",
+                "a10.sql",
+                "b.sql"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_schemas_accept_a_list() {
+        let settings = |yaml: &str| {
+            config::Config::builder()
+                .add_source(config::File::from_str(yaml, config::FileFormat::Yaml))
+                .build()
+                .unwrap()
+                .try_deserialize::<Settings>()
+                .unwrap()
+        };
+
+        let single = settings("sql:\n  schemas: db\n  queries: q\n  output: o\n");
+        assert_eq!(single.sql[0].schemas, vec!["db"]);
+
+        let list = settings(
+            "sql:\n  schemas:\n    - db/init.sql\n    - db/migrations/*.sql\n  queries: q\n  output: o\n",
+        );
+        assert_eq!(
+            list.sql[0].schemas,
+            vec!["db/init.sql", "db/migrations/*.sql"]
+        );
+        assert_eq!(list.sql[0].types, TypeSettings::default());
+
+        let types = settings(
+            "sql:\n  schemas: db\n  queries: q\n  output: o\n  types:\n    decimal: big_decimal\n",
+        );
+        assert_eq!(types.sql[0].types.decimal, DecimalStrategy::BigDecimal);
+        assert_eq!(
+            types.sql[0].types.unsigned_bigint,
+            UnsignedBigIntStrategy::String
+        );
+
+        assert_eq!(list.sql[0].php_version, PhpVersion::new(8, 2));
+        let legacy =
+            settings("sql:\n  schemas: db\n  queries: q\n  output: o\n  php_version: 7.3\n");
+        assert_eq!(legacy.sql[0].php_version, PhpVersion::new(7, 3));
+        assert_eq!("8".parse::<PhpVersion>().unwrap(), PhpVersion::new(8, 0));
+        assert!("7.0".parse::<PhpVersion>().is_err());
+        assert!("eight".parse::<PhpVersion>().is_err());
+    }
+
+    #[test]
+    fn test_sql_accepts_a_list_of_packages() {
+        let settings = config::Config::builder()
+            .add_source(config::File::from_str(
+                "sql:
+  - name: billing
+    engine: postgresql
+    schemas: billing/schema
+    queries: billing/queries
+    output: src/Billing
+    namespace: App\\Billing
+  - name: auth
+    schemas: auth/schema
+    queries: auth/queries
+    output: src/Auth
+",
+                config::FileFormat::Yaml,
+            ))
+            .build()
+            .unwrap()
+            .try_deserialize::<Settings>()
+            .unwrap();
+
+        assert_eq!(settings.sql.len(), 2);
+        assert_eq!(settings.sql[0].engine, Engine::PostgreSql);
+        assert_eq!(settings.sql[1].engine, Engine::MySql);
+        assert_eq!(settings.packages(None).unwrap().len(), 2);
+
+        let auth = settings.packages(Some("auth")).unwrap();
+        assert_eq!(auth.len(), 1);
+        assert_eq!(auth[0].output, "src/Auth");
+
+        let error = settings.packages(Some("shop")).err().unwrap();
+        assert!(error.to_string().contains("billing, auth"));
+
+        let missing = config::Config::builder()
+            .add_source(config::File::from_str(
+                "sql:\n  schemas: db\n  output: o\n",
+                config::FileFormat::Yaml,
+            ))
+            .build()
+            .unwrap()
+            .try_deserialize::<Settings>()
+            .err()
+            .unwrap();
+        assert!(missing.to_string().contains("queries"));
+    }
+
+    #[test]
+    fn test_split_statements_keep_literals_as_written() {
+        let sql = "CREATE TABLE users (name TEXT DEFAULT 'O''Brien');
+
+-- seed
+INSERT INTO users (name) VALUES ('a\\'b');";
+        let statements = SqlFileParser::new(Engine::MySql)
+            .split_statements(sql)
+            .unwrap();
+        assert_eq!(
+            statements,
+            vec![
+                (
+                    1,
+                    "CREATE TABLE users (name TEXT DEFAULT 'O''Brien')".to_string()
+                ),
+                (4, "INSERT INTO users (name) VALUES ('a\\'b')".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_migrations_only_apply_up_sections() {
+        let dbmate = "-- migrate:up
+CREATE TABLE users (id INT, name TEXT);
+
+-- migrate:down
+DROP TABLE users;";
+        let tables = process_sql_file(dbmate.to_string()).unwrap().tables;
+        assert_eq!(tables.len(), 1);
+
+        let goose = "-- +goose Up
+-- +goose StatementBegin
+CREATE TABLE users (id INT, name TEXT);
+-- +goose StatementEnd
+
+-- +goose Down
+ALTER TABLE users DROP COLUMN name;";
+        let tables = process_sql_file(goose.to_string()).unwrap().tables;
+        assert_eq!(tables[0].columns.len(), 2);
+
+        // Problems in an up section still point at the line in the file
+        let error = process_sql_file(
+            "-- migrate:down\nDROP TABLE users;\n-- migrate:up\nALTER TABLE users ADD COLUMN x INT;"
+                .to_string(),
+        )
+        .err()
+        .unwrap()
+        .to_string();
+        assert!(error.contains("--> 4:13"), "{}", error);
+    }
+
+    #[test]
+    fn test_golang_migrate_down_files_are_skipped() {
+        let dir = "target/test-output/golang_migrate";
+        let _ = fs::remove_dir_all(dir);
+        fs::create_dir_all(dir).unwrap();
+        fs::write(
+            format!("{}/1_create_users.up.sql", dir),
+            "CREATE TABLE users (id INT);",
+        )
+        .unwrap();
+        fs::write(
+            format!("{}/1_create_users.down.sql", dir),
+            "DROP TABLE users;",
+        )
+        .unwrap();
+        fs::write(
+            format!("{}/2_add_name.up.sql", dir),
+            "ALTER TABLE users ADD COLUMN name TEXT;",
+        )
+        .unwrap();
+        fs::write(
+            format!("{}/2_add_name.down.sql", dir),
+            "ALTER TABLE users DROP COLUMN name;",
+        )
+        .unwrap();
+
+        let sql_files = SqlFileParser::default()
+            .parse_paths(&[dir.to_string()])
+            .unwrap();
+        let paths: Vec<&str> = sql_files.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "target/test-output/golang_migrate/1_create_users.up.sql",
+                "target/test-output/golang_migrate/2_add_name.up.sql"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_constraints_keys_and_indexes() {
+        let content = "CREATE TABLE users (id INT PRIMARY KEY, email VARCHAR(255) UNIQUE);
+CREATE TABLE memberships (
+    user_id INT REFERENCES users ON DELETE CASCADE,
+    group_id INT,
+    role VARCHAR(20) CHECK (role <> ''),
+    PRIMARY KEY (user_id, group_id),
+    CONSTRAINT fk_parent FOREIGN KEY (group_id) REFERENCES memberships (user_id) ON UPDATE SET NULL,
+    UNIQUE KEY uq_role (user_id, role),
+    KEY idx_role (role)
+);
+CREATE UNIQUE INDEX idx_email ON users (email);
+CREATE INDEX idx_lower_email ON users (LOWER(email));";
+        let engine_data = process_sql_file(content.to_string()).unwrap();
+        let users = engine_data.find_table("users").unwrap();
+        assert_eq!(users.primary_key, vec!["id"]);
+        assert_eq!(users.unique_constraints[0].columns, vec!["email"]);
+        assert_eq!(users.indexes.len(), 2);
+        assert!(users.indexes[0].unique);
+        assert_eq!(users.indexes[1].columns, vec!["LOWER(email)"]);
+
+        let memberships = engine_data.find_table("memberships").unwrap();
+        assert_eq!(memberships.primary_key, vec!["user_id", "group_id"]);
+        // A composite table level primary key makes its columns NOT NULL
+        assert!(!memberships.columns[1].data_type.nullable);
+
+        let user = &memberships.foreign_keys[0];
+        assert_eq!(user.referenced_table, "users");
+        assert_eq!(user.referenced_columns, vec!["id"]);
+        assert_eq!(user.on_delete, Some(ReferentialAction::Cascade));
+        let parent = &memberships.foreign_keys[1];
+        assert_eq!(parent.name.as_deref(), Some("fk_parent"));
+        assert_eq!(parent.on_update, Some(ReferentialAction::SetNull));
+
+        assert_eq!(memberships.checks[0].expr, "role <> ''");
+        assert_eq!(
+            memberships.unique_constraints[0].name.as_deref(),
+            Some("uq_role")
+        );
+        assert_eq!(memberships.indexes[0].columns, vec!["role"]);
+    }
+
+    #[test]
+    fn test_quoted_identifiers_are_recorded_unquoted() {
+        let content = "CREATE TABLE `a` (`id` INT NOT NULL, `name` TEXT, PRIMARY KEY (`id`));
+CREATE TABLE `b` (`a_id` INT, FOREIGN KEY (`a_id`) REFERENCES `a` (`id`));
+CREATE INDEX ix ON `a` (`id`);";
+        let engine_data = process_sql_file(content.to_string()).unwrap();
+        let a = engine_data.find_table("a").unwrap();
+        assert_eq!(a.columns[0].name, "id");
+        assert_eq!(a.primary_key, vec!["id"]);
+        assert_eq!(a.indexes[0].columns, vec!["id"]);
+        let b = engine_data.find_table("b").unwrap();
+        assert_eq!(b.foreign_keys[0].referenced_table, "a");
+        assert_eq!(b.foreign_keys[0].referenced_columns, vec!["id"]);
+
+        let content = r#"CREATE TABLE "public"."users" ("id" SERIAL PRIMARY KEY, "email" TEXT);
+CREATE UNIQUE INDEX "ix_email" ON "users" ("email");
+DROP INDEX "ix_email";"#;
+        let engine_data =
+            process_sql_file_for_engine(content.to_string(), Engine::PostgreSql).unwrap();
+        let users = engine_data.find_table("users").unwrap();
+        assert_eq!(users.primary_key, vec!["id"]);
+        assert!(users.indexes.is_empty());
+    }
+
+    #[test]
+    fn test_migrations_update_constraints() {
+        let content = "CREATE TABLE users (id INT PRIMARY KEY, email TEXT);
+CREATE TABLE posts (id INT PRIMARY KEY, author INT, title TEXT);
+ALTER TABLE posts ADD CONSTRAINT fk_author FOREIGN KEY (author) REFERENCES users (id);
+ALTER TABLE posts ADD CONSTRAINT uq_title UNIQUE (title);
+ALTER TABLE posts RENAME COLUMN author TO author_id;
+ALTER TABLE posts DROP CONSTRAINT uq_title;
+ALTER TABLE users RENAME TO members;
+CREATE INDEX idx_email ON members (email);
+DROP INDEX idx_email;";
+        let engine_data =
+            process_sql_file_for_engine(content.to_string(), Engine::PostgreSql).unwrap();
+        let posts = engine_data.find_table("posts").unwrap();
+        assert_eq!(posts.foreign_keys[0].columns, vec!["author_id"]);
+        assert_eq!(posts.foreign_keys[0].referenced_table, "members");
+        assert!(posts.unique_constraints.is_empty());
+        assert!(
+            engine_data
+                .find_table("members")
+                .unwrap()
+                .indexes
+                .is_empty()
+        );
+
+        let content = "CREATE TABLE users (id INT, email TEXT);
+CREATE TABLE posts (id INT, title TEXT);
+CREATE INDEX idx_email ON users (email);
+CREATE INDEX IF NOT EXISTS idx_email ON users (email);
+CREATE INDEX idx_email ON posts (title);";
+        let error = process_sql_file_for_engine(content.to_string(), Engine::PostgreSql)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(
+            error.contains("index idx_email already exists"),
+            "{}",
+            error
+        );
+        assert!(error.contains("--> 5:14"), "{}", error);
+        // MySQL only needs the name to be unique on the table
+        let engine_data = process_sql_file(content.to_string()).unwrap();
+        assert_eq!(engine_data.find_table("users").unwrap().indexes.len(), 1);
+
+        let error = process_sql_file(
+            "CREATE TABLE posts (id INT, author INT REFERENCES users (id));".to_string(),
+        )
+        .err()
+        .unwrap()
+        .to_string();
+        assert!(
+            error.contains("foreign key references unknown table users"),
+            "{}",
+            error
+        );
+
+        let error = process_sql_file(
+            "CREATE TABLE users (id INT);\nCREATE INDEX idx ON users (missing);".to_string(),
+        )
+        .err()
+        .unwrap()
+        .to_string();
+        assert!(
+            error.contains("table users has no column missing"),
+            "{}",
+            error
+        );
+        assert!(error.contains("--> 2:28"), "{}", error);
+    }
+
+    #[test]
+    fn test_defaults_and_generated_columns() {
+        let content = "CREATE TABLE users (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    status VARCHAR(20) NOT NULL DEFAULT 'active',
+    score INT DEFAULT -1,
+    verified TINYINT(1) NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    full_name VARCHAR(255) GENERATED ALWAYS AS (CONCAT(status, '!')),
+    note TEXT
+);
+ALTER TABLE users ALTER COLUMN note SET DEFAULT 'none';";
+        let tables = process_sql_file(content.to_string()).unwrap().tables;
+        let columns = &tables[0].columns;
+
+        assert_eq!(columns[0].generated, Some(Generated::AutoIncrement));
+        let php_values: Vec<Option<&str>> = columns
+            .iter()
+            .map(|column| column.default.as_ref()?.php_value.as_deref())
+            .collect();
+        assert_eq!(
+            php_values,
+            vec![
+                None,
+                Some("'active'"),
+                Some("-1"),
+                Some("false"),
+                None,
+                None,
+                Some("'none'")
+            ]
+        );
+        assert!(columns[4].has_default());
+        assert!(matches!(columns[5].generated, Some(Generated::Computed(_))));
+        assert!(columns[6].generated.is_none() && columns[6].has_default());
+
+        let content = "CREATE TABLE events (id SERIAL, uid INT GENERATED ALWAYS AS IDENTITY);";
+        let tables = process_sql_file_for_engine(content.to_string(), Engine::PostgreSql)
+            .unwrap()
+            .tables;
+        assert_eq!(
+            tables[0].columns[0].generated,
+            Some(Generated::AutoIncrement)
+        );
+        assert_eq!(tables[0].columns[1].generated, Some(Generated::Identity));
+
+        let content = "CREATE TABLE readings (
+    ratio DOUBLE PRECISION DEFAULT 'NaN',
+    peak REAL DEFAULT '-Infinity',
+    base REAL DEFAULT 1.5
+);";
+        let tables = process_sql_file_for_engine(content.to_string(), Engine::PostgreSql)
+            .unwrap()
+            .tables;
+        let php_values: Vec<Option<&str>> = tables[0]
+            .columns
+            .iter()
+            .map(|column| column.default.as_ref()?.php_value.as_deref())
+            .collect();
+        assert_eq!(php_values, vec![None, None, Some("1.5")]);
+    }
+
+    #[test]
+    fn test_enum_types() {
+        let content = "CREATE TYPE mood AS ENUM ('sad', 'ok');
+ALTER TYPE mood ADD VALUE 'happy' AFTER 'ok';
+ALTER TYPE mood RENAME VALUE 'sad' TO 'blue';
+CREATE TABLE people (id SERIAL PRIMARY KEY, current_mood mood, previous mood NOT NULL);
+ALTER TYPE mood RENAME TO feeling;";
+        let engine_data =
+            process_sql_file_for_engine(content.to_string(), Engine::PostgreSql).unwrap();
+        assert_eq!(engine_data.enums[0].name, "feeling");
+        assert_eq!(engine_data.enums[0].values, vec!["blue", "ok", "happy"]);
+        let columns = &engine_data.tables[0].columns;
+        assert_eq!(columns[1].data_type.php_type.type_hint, "?Feeling");
+        assert_eq!(columns[2].data_type.php_type.type_hint, "Feeling");
+
+        // Columns keep a type from being dropped, unless CASCADE drops them with it
+        let error = process_sql_file_for_engine(
+            format!("{}\nDROP TYPE feeling;", content),
+            Engine::PostgreSql,
+        )
+        .err()
+        .unwrap()
+        .to_string();
+        assert!(
+            error.contains(
+                "cannot drop type feeling, people.current_mood, people.previous still use it"
+            ),
+            "{}",
+            error
+        );
+        let engine_data = process_sql_file_for_engine(
+            format!("{}\nDROP TYPE feeling CASCADE;", content),
+            Engine::PostgreSql,
+        )
+        .unwrap();
+        assert!(engine_data.enums.is_empty());
+        assert_eq!(engine_data.tables[0].columns.len(), 1);
+
+        let content =
+            "CREATE TABLE tasks (id INT, state ENUM('to-do', 'done') NOT NULL DEFAULT 'to-do');";
+        let tables = process_sql_file(content.to_string()).unwrap().tables;
+        let state = &tables[0].columns[1];
+        assert_eq!(
+            state.data_type.php_type.php_type,
+            Enum("TasksState".to_string())
+        );
+        assert_eq!(
+            state.default.as_ref().unwrap().php_value.as_deref(),
+            Some("TasksState::ToDo")
+        );
+    }
 }
@@ -0,0 +1,620 @@
+#[cfg(test)]
+mod tests {
+    use bde::analyzer::{process_query_file, process_sql_file_with_types};
+    use bde::configuration::{
+        DecimalStrategy, Engine, EntityStyle, OutputLayout, PhpVersion, SqlSettings,
+    };
+    use bde::generator::TemplateEngine;
+    use std::fs;
+    use std::path::Path;
+
+    fn generate(output: &str, queries: &str) {
+        let schema = fs::read_to_string("examples/schemas/schema.sql").unwrap();
+        generate_with_schema(output, &schema, queries);
+    }
+
+    fn generate_with_schema(output: &str, schema: &str, queries: &str) {
+        generate_with_settings(output, schema, queries, |_| {});
+    }
+
+    fn generate_with_settings(
+        output: &str,
+        schema: &str,
+        queries: &str,
+        configure: impl FnOnce(&mut SqlSettings),
+    ) {
+        try_generate(output, schema, queries, configure).unwrap();
+    }
+
+    fn try_generate(
+        output: &str,
+        schema: &str,
+        queries: &str,
+        configure: impl FnOnce(&mut SqlSettings),
+    ) -> anyhow::Result<()> {
+        let mut settings = SqlSettings {
+            name: None,
+            schemas: vec!["examples/schemas".to_string()],
+            queries: "examples/queries".to_string(),
+            output: output.to_string(),
+            namespace: Some("App\\Db".to_string()),
+            engine: Engine::default(),
+            verify: Default::default(),
+            types: Default::default(),
+            entity_style: Default::default(),
+            php_version: Default::default(),
+            layout: Default::default(),
+            composer: None,
+        };
+        configure(&mut settings);
+
+        let engine_data =
+            process_sql_file_with_types(schema.to_string(), settings.engine, settings.types)?;
+        let queries = process_query_file(queries.to_string(), &engine_data)?;
+
+        let _ = fs::remove_dir_all(output);
+        TemplateEngine::new(engine_data, queries, &settings)?.generate_templates()
+    }
+
+    #[test]
+    fn test_row_classes_are_generated_for_custom_projections() {
+        let output = "target/test-output/row_classes";
+        generate(
+            output,
+            "-- name: GetUser :one
+SELECT * FROM users WHERE id = :id;
+
+-- name: ListProviders :many
+SELECT u.username, o.provider FROM users u LEFT JOIN oauth o ON o.user_id = u.id;",
+        );
+
+        assert!(Path::new(output).join("Users.php").exists());
+        assert!(!Path::new(output).join("GetUserRow.php").exists());
+
+        let row = fs::read_to_string(Path::new(output).join("ListProvidersRow.php")).unwrap();
+        assert!(row.contains("class ListProvidersRow"));
+        assert!(row.contains("public ?string $provider;"));
+
+        let queries = fs::read_to_string(Path::new(output).join("Queries.php")).unwrap();
+        assert!(queries.contains("public function getUser(int $id): ?Users"));
+        assert!(queries.contains("$result[] = ListProvidersRow::fromRow($row);"));
+    }
+
+    #[test]
+    fn test_batch_queries_loop_over_parameter_sets() {
+        let output = "target/test-output/batch_queries";
+        generate(
+            output,
+            "-- name: GetUsers :batchone
+SELECT * FROM users WHERE id = :id;
+
+-- name: DeleteUsers :batchexec
+DELETE FROM users WHERE id = :id;",
+        );
+
+        let queries = fs::read_to_string(Path::new(output).join("Queries.php")).unwrap();
+        assert!(queries.contains("@param list<array{id: int}> $batch"));
+        assert!(queries.contains("public function getUsers(array $batch): array"));
+        assert!(queries.contains("$stmt->bindValue(1, $params['id'], PDO::PARAM_INT);"));
+        assert!(queries.contains("@return list<Users|null>"));
+    }
+
+    #[test]
+    fn test_constant_defaults_make_insert_parameters_optional() {
+        let output = "target/test-output/defaults";
+        generate_with_schema(
+            output,
+            "CREATE TABLE posts (
+    id INT AUTO_INCREMENT PRIMARY KEY,
+    title VARCHAR(255) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'draft',
+    views INT NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);",
+            "-- name: CreatePost :execlastid
+INSERT INTO posts (title, status, views) VALUES (:title, :status, :views);
+
+-- name: CreateDraft :exec
+INSERT INTO posts (status, title) VALUES (:status, :title);",
+        );
+
+        let entity = fs::read_to_string(Path::new(output).join("Posts.php")).unwrap();
+        assert!(entity.contains("public const DEFAULT_STATUS = 'draft';"));
+        assert!(entity.contains("public string $status = self::DEFAULT_STATUS;"));
+        assert!(entity.contains("public int $views = self::DEFAULT_VIEWS;"));
+        assert!(!entity.contains("DEFAULT_CREATED_AT"));
+
+        let queries = fs::read_to_string(Path::new(output).join("Queries.php")).unwrap();
+        assert!(
+            queries.contains(
+                "createPost(string $title, string $status = 'draft', int $views = 0): int"
+            )
+        );
+        // A required parameter follows, so the default cannot be used
+        assert!(queries.contains("createDraft(string $status, string $title): void"));
+    }
+
+    #[test]
+    fn test_enums_are_generated_and_bound_by_value() {
+        let output = "target/test-output/enums";
+        generate_with_schema(
+            output,
+            "CREATE TABLE tasks (id INT PRIMARY KEY, state ENUM('to-do', 'done') NOT NULL, previous ENUM('to-do', 'done'));",
+            "-- name: GetTask :one
+SELECT * FROM tasks WHERE id = :id;
+
+-- name: SetStates :batchexec
+UPDATE tasks SET state = :state, previous = :previous WHERE id = :id;",
+        );
+
+        let state = fs::read_to_string(Path::new(output).join("TasksState.php")).unwrap();
+        assert!(state.contains("enum TasksState: string"));
+        assert!(state.contains("case ToDo = 'to-do';"));
+
+        let entity = fs::read_to_string(Path::new(output).join("Tasks.php")).unwrap();
+        assert!(entity.contains("public ?TasksPrevious $previous;"));
+
+        let queries = fs::read_to_string(Path::new(output).join("Queries.php")).unwrap();
+        assert!(entity.contains("$entity->state = TasksState::from($row['state']);"));
+        assert!(entity.contains("$entity->previous = $row['previous'] === null ? null : TasksPrevious::from($row['previous']);"
+        ));
+        assert!(queries.contains("$stmt->bindValue(1, $params['state']->value, PDO::PARAM_STR);"));
+        assert!(
+            queries.contains("$stmt->bindValue(2, $params['previous']?->value, PDO::PARAM_STR);")
+        );
+    }
+
+    #[test]
+    fn test_clashing_and_reserved_names_are_rejected() {
+        let error = try_generate(
+            "target/test-output/clashes",
+            "CREATE TABLE a (s ENUM('a-b', 'a_b', 'A B', 'class'));
+CREATE TABLE tasks (state ENUM('open', 'closed'));
+CREATE TABLE tasks_state (id INT);
+CREATE TABLE tasks_s (tate ENUM('x'));",
+            "",
+            |_| {},
+        )
+        .err()
+        .unwrap()
+        .to_string();
+        assert!(
+            error.contains("column a.s would generate class AS, which is a reserved word in PHP"),
+            "{}",
+            error
+        );
+        assert!(
+            error.contains("values 'a-b' and 'a_b' of column a.s would both generate case AB"),
+            "{}",
+            error
+        );
+        assert!(
+            error.contains("values 'a-b' and 'A B' of column a.s would both generate case AB"),
+            "{}",
+            error
+        );
+        assert!(
+            error.contains("value 'class' of column a.s would generate case Class"),
+            "{}",
+            error
+        );
+        assert!(
+            error.contains(
+                "column tasks.state and column tasks_s.tate would both generate class TasksSTate"
+            ),
+            "{}",
+            error
+        );
+        assert!(
+            error.contains(
+                "column tasks.state and table tasks_state would both generate class TasksState"
+            ),
+            "{}",
+            error
+        );
+
+        // Entities and enums only share a namespace in the flat layout
+        let output = "target/test-output/no_clashes";
+        generate_with_settings(
+            output,
+            "CREATE TABLE tasks (state ENUM('open', 'closed'));
+CREATE TABLE tasks_state (id INT);",
+            "",
+            |settings| settings.layout = OutputLayout::Psr4,
+        );
+        assert!(Path::new(output).join("Enum/TasksState.php").exists());
+        assert!(Path::new(output).join("Entity/TasksState.php").exists());
+    }
+
+    #[test]
+    fn test_postgres_arrays_are_passed_as_text() {
+        let output = "target/test-output/arrays";
+        generate_with_settings(
+            output,
+            "CREATE TABLE posts (id SERIAL PRIMARY KEY, tags TEXT[]);",
+            "-- name: GetPost :one
+SELECT * FROM posts WHERE id = :id;
+
+-- name: SetTags :exec
+UPDATE posts SET tags = :tags WHERE id = :id;",
+            |settings| settings.engine = Engine::PostgreSql,
+        );
+
+        let entity = fs::read_to_string(Path::new(output).join("Posts.php")).unwrap();
+        assert!(entity.contains("public ?string $tags;"));
+        assert!(entity.contains("$entity->tags = $row['tags'] === null ? null : $row['tags'];"));
+        assert!(entity.contains("'tags' => $this->tags,"));
+
+        let queries = fs::read_to_string(Path::new(output).join("Queries.php")).unwrap();
+        assert!(queries.contains("public function setTags(?string $tags, int $id): void"));
+    }
+
+    #[test]
+    fn test_parameters_are_bound_with_their_pdo_type() {
+        let output = "target/test-output/bind_types";
+        generate_with_schema(
+            output,
+            "CREATE TABLE posts (id INT PRIMARY KEY, title TEXT NOT NULL, published BOOLEAN NOT NULL);",
+            "-- name: ListPosts :many
+SELECT * FROM posts WHERE title = :title AND published = :published LIMIT ? OFFSET ?;",
+        );
+
+        let queries = fs::read_to_string(Path::new(output).join("Queries.php")).unwrap();
+        assert!(queries.contains("$stmt->bindValue(1, $title, PDO::PARAM_STR);"));
+        assert!(queries.contains("$stmt->bindValue(2, $published, PDO::PARAM_BOOL);"));
+        assert!(queries.contains("$stmt->bindValue(3, $limit, PDO::PARAM_INT);"));
+        assert!(queries.contains("$stmt->bindValue(4, $offset, PDO::PARAM_INT);"));
+    }
+
+    #[test]
+    fn test_sets_are_lists_of_enum_cases() {
+        let output = "target/test-output/sets";
+        generate_with_schema(
+            output,
+            "CREATE TABLE grants (id INT PRIMARY KEY, permissions SET('read', 'write', 'admin') NOT NULL DEFAULT 'read,write');",
+            "-- name: GetGrant :one
+SELECT * FROM grants WHERE id = :id;
+
+-- name: SetPermissions :exec
+UPDATE grants SET permissions = :permissions WHERE id = :id;",
+        );
+
+        let permissions =
+            fs::read_to_string(Path::new(output).join("GrantsPermissions.php")).unwrap();
+        assert!(permissions.contains("case Admin = 'admin';"));
+
+        let entity = fs::read_to_string(Path::new(output).join("Grants.php")).unwrap();
+        assert!(entity.contains("@var list<GrantsPermissions>"));
+        assert!(entity.contains(
+            "public const DEFAULT_PERMISSIONS = [GrantsPermissions::Read, GrantsPermissions::Write];"
+        ));
+        assert!(entity.contains("public array $permissions = self::DEFAULT_PERMISSIONS;"));
+
+        let queries = fs::read_to_string(Path::new(output).join("Queries.php")).unwrap();
+        assert!(entity.contains("$entity->permissions = ($row['permissions'] === '' ? [] : array_map(GrantsPermissions::from(...), explode(',', $row['permissions'])));"
+        ));
+        assert!(queries.contains("@param list<GrantsPermissions> $permissions"));
+        assert!(queries.contains(
+            "$stmt->bindValue(1, implode(',', array_column($permissions, 'value')), PDO::PARAM_STR);"
+        ));
+    }
+
+    #[test]
+    fn test_decimals_can_be_big_decimals() {
+        let output = "target/test-output/big_decimals";
+        generate_with_settings(
+            output,
+            "CREATE TABLE products (id INT PRIMARY KEY, price DECIMAL(10, 2) NOT NULL, discount DECIMAL(10, 2));",
+            "-- name: GetProduct :one
+SELECT * FROM products WHERE id = :id;
+
+-- name: UpdatePrice :exec
+UPDATE products SET price = :price, discount = :discount WHERE id = :id;",
+            |settings| settings.types.decimal = DecimalStrategy::BigDecimal,
+        );
+
+        let entity = fs::read_to_string(Path::new(output).join("Products.php")).unwrap();
+        assert!(entity.contains("use Brick\\Math\\BigDecimal;"));
+        assert!(entity.contains("public BigDecimal $price;"));
+
+        let queries = fs::read_to_string(Path::new(output).join("Queries.php")).unwrap();
+        assert!(entity.contains("$entity->price = BigDecimal::of($row['price']);"));
+        assert!(queries.contains("$stmt->bindValue(1, (string) $price, PDO::PARAM_STR);"));
+        assert!(queries.contains(
+            "$stmt->bindValue(2, $discount === null ? null : (string) $discount, PDO::PARAM_STR);"
+        ));
+    }
+
+    #[test]
+    fn test_dates_are_hydrated_and_bound_in_their_format() {
+        let output = "target/test-output/dates";
+        generate_with_schema(
+            output,
+            "CREATE TABLE events (id INT PRIMARY KEY, day DATE NOT NULL, starts_at DATETIME(3));",
+            "-- name: GetEvent :one
+SELECT * FROM events WHERE id = :id;
+
+-- name: Reschedule :exec
+UPDATE events SET day = :day, starts_at = :starts_at WHERE id = :id;",
+        );
+
+        let entity = fs::read_to_string(Path::new(output).join("Events.php")).unwrap();
+        assert!(entity.contains("public DateTimeImmutable $day;"));
+
+        let queries = fs::read_to_string(Path::new(output).join("Queries.php")).unwrap();
+        assert!(entity.contains(
+            "$entity->day = DateTimeImmutable::createFromFormat('!Y-m-d', $row['day']);"
+        ));
+        assert!(entity.contains("$entity->starts_at = $row['starts_at'] === null ? null : new DateTimeImmutable($row['starts_at']);"
+        ));
+        assert!(queries.contains("$stmt->bindValue(1, $day->format('Y-m-d'), PDO::PARAM_STR);"));
+        assert!(queries.contains(
+            "$stmt->bindValue(2, $starts_at?->format('Y-m-d H:i:s.u'), PDO::PARAM_STR);"
+        ));
+    }
+
+    #[test]
+    fn test_external_classes_are_imported() {
+        let output = "target/test-output/imports";
+        generate_with_schema(
+            output,
+            "CREATE TABLE pdo (id INT PRIMARY KEY, created_at DATETIME NOT NULL);",
+            "-- name: GetPdo :one
+SELECT * FROM pdo WHERE id = :id;
+
+-- name: DeletePdo :execresult
+DELETE FROM pdo WHERE id = :id;",
+        );
+
+        let entity = fs::read_to_string(Path::new(output).join("Pdo.php")).unwrap();
+        assert!(entity.contains("namespace App\\Db;\n\n\nuse DateTimeImmutable;\n\nclass Pdo"));
+        assert!(entity.contains("public DateTimeImmutable $created_at;"));
+
+        // PHP class names are case insensitive, so the entity Pdo takes the name of PDO
+        let queries = fs::read_to_string(Path::new(output).join("Queries.php")).unwrap();
+        assert!(queries.contains("namespace App\\Db;\n\n\nuse PDOStatement;\n\nclass Queries"));
+        assert!(queries.contains("public function __construct(\\PDO $pdo)"));
+        assert!(queries.contains("$row = $stmt->fetch(\\PDO::FETCH_ASSOC);"));
+        assert!(queries.contains("public function deletePdo(int $id): PDOStatement"));
+    }
+
+    #[test]
+    fn test_entities_convert_rows_both_ways() {
+        let output = "target/test-output/from_row";
+        generate_with_schema(
+            output,
+            "CREATE TABLE accounts (id INT PRIMARY KEY, active BOOLEAN NOT NULL, settings JSON, joined DATE NOT NULL, plan ENUM('free', 'paid'));",
+            "-- name: GetAccount :one
+SELECT * FROM accounts WHERE id = :id;
+
+-- name: ListAccounts :many
+SELECT * FROM accounts;",
+        );
+
+        let entity = fs::read_to_string(Path::new(output).join("Accounts.php")).unwrap();
+        assert!(entity.contains("public static function fromRow(array $row): self"));
+        assert!(entity.contains(
+            "$entity->active = in_array($row['active'], [true, 1, '1', 't', 'true'], true);"
+        ));
+        assert!(entity.contains(
+            "$entity->settings = $row['settings'] === null ? null : json_decode($row['settings'], true);"
+        ));
+        assert!(entity.contains("public function toArray(): array"));
+        assert!(entity.contains("'active' => (int) $this->active,"));
+        assert!(entity.contains(
+            "'settings' => $this->settings === null ? null : json_encode($this->settings),"
+        ));
+        assert!(entity.contains("'joined' => $this->joined->format('Y-m-d'),"));
+        assert!(entity.contains("'plan' => $this->plan?->value,"));
+
+        let queries = fs::read_to_string(Path::new(output).join("Queries.php")).unwrap();
+        assert!(queries.contains("return Accounts::fromRow($row);"));
+        assert!(queries.contains("$result[] = Accounts::fromRow($row);"));
+        assert!(!queries.contains("$row['"));
+    }
+
+    #[test]
+    fn test_readonly_entities_are_promoted_with_required_parameters_first() {
+        let output = "target/test-output/readonly";
+        generate_with_settings(
+            output,
+            "CREATE TABLE posts (
+    id INT AUTO_INCREMENT PRIMARY KEY,
+    subtitle VARCHAR(255),
+    status VARCHAR(20) NOT NULL DEFAULT 'draft',
+    title VARCHAR(255) NOT NULL
+);",
+            "-- name: GetPost :one
+SELECT * FROM posts WHERE id = :id;",
+            |settings| settings.entity_style = EntityStyle::Readonly,
+        );
+
+        let entity = fs::read_to_string(Path::new(output).join("Posts.php")).unwrap();
+        assert!(entity.contains("final readonly class Posts"));
+        assert!(entity.contains(
+            "    public function __construct(
+        public int $id,
+        public string $title,
+        public ?string $subtitle = null,
+        public string $status = self::DEFAULT_STATUS,
+    ) {
+    }"
+        ));
+        assert!(entity.contains("return new self(\n            id: (int) $row['id'],"));
+        assert!(entity.contains("public function withSubtitle(?string $subtitle): self"));
+        assert!(
+            entity.contains(
+                "return new self(...['subtitle' => $subtitle] + get_object_vars($this));"
+            )
+        );
+
+        let queries = fs::read_to_string(Path::new(output).join("Queries.php")).unwrap();
+        assert!(queries.contains("return Posts::fromRow($row);"));
+    }
+
+    #[test]
+    fn test_legacy_php_gets_untyped_properties_and_constant_classes() {
+        let output = "target/test-output/php73";
+        generate_with_settings(
+            output,
+            "CREATE TABLE tasks (
+    id INT PRIMARY KEY,
+    state ENUM('to-do', 'done') NOT NULL DEFAULT 'to-do',
+    labels SET('urgent', 'later'),
+    due DATETIME
+);",
+            "-- name: GetTask :one
+SELECT * FROM tasks WHERE id = :id;
+
+-- name: UpdateTask :exec
+UPDATE tasks SET state = :state, labels = :labels, due = :due WHERE id = :id;",
+            |settings| settings.php_version = PhpVersion::new(7, 3),
+        );
+
+        let state = fs::read_to_string(Path::new(output).join("TasksState.php")).unwrap();
+        assert!(state.contains("final class TasksState"));
+        assert!(state.contains("public const ToDo = 'to-do';"));
+
+        let entity = fs::read_to_string(Path::new(output).join("Tasks.php")).unwrap();
+        assert!(entity.contains("public const DEFAULT_STATE = TasksState::ToDo;"));
+        assert!(entity.contains("@var string\n     */\n    public $state = self::DEFAULT_STATE;"));
+        assert!(entity.contains("@var list<string>|null\n     */\n    public $labels;"));
+        assert!(entity.contains("$entity->state = $row['state'];"));
+        assert!(entity.contains(
+            "$entity->labels = $row['labels'] === null ? null : ($row['labels'] === '' ? [] : explode(',', $row['labels']));"
+        ));
+
+        let queries = fs::read_to_string(Path::new(output).join("Queries.php")).unwrap();
+        assert!(queries.contains("private $pdo;"));
+        assert!(queries.contains(
+            "public function updateTask(string $state, ?array $labels, ?DateTimeImmutable $due, int $id): void"
+        ));
+        assert!(queries.contains(
+            "$stmt->bindValue(3, $due === null ? null : $due->format('Y-m-d H:i:s'), PDO::PARAM_STR);"
+        ));
+        assert!(queries.contains("$stmt->bindValue(4, $id, PDO::PARAM_INT);"));
+    }
+
+    #[test]
+    fn test_php_83_types_class_constants() {
+        let output = "target/test-output/php83";
+        generate_with_settings(
+            output,
+            "CREATE TABLE posts (id INT PRIMARY KEY, status VARCHAR(20) NOT NULL DEFAULT 'draft');",
+            "-- name: GetPost :one
+SELECT * FROM posts WHERE id = :id;",
+            |settings| {
+                settings.php_version = PhpVersion::new(8, 3);
+                settings.entity_style = EntityStyle::Readonly;
+            },
+        );
+
+        let entity = fs::read_to_string(Path::new(output).join("Posts.php")).unwrap();
+        assert!(entity.contains("final readonly class Posts"));
+        assert!(entity.contains("public const string DEFAULT_STATUS = 'draft';"));
+    }
+
+    #[test]
+    fn test_readonly_entities_need_php_81() {
+        let schema = "CREATE TABLE posts (id INT PRIMARY KEY, title VARCHAR(255) NOT NULL);";
+        let queries = "-- name: GetPost :one
+SELECT * FROM posts WHERE id = :id;";
+
+        generate_with_settings("target/test-output/php81", schema, queries, |settings| {
+            settings.php_version = PhpVersion::new(8, 1);
+            settings.entity_style = EntityStyle::Readonly;
+        });
+        let entity =
+            fs::read_to_string(Path::new("target/test-output/php81").join("Posts.php")).unwrap();
+        assert!(entity.contains("final class Posts"));
+        assert!(entity.contains("public readonly int $id,"));
+
+        let error = try_generate("target/test-output/php80", schema, queries, |settings| {
+            settings.php_version = PhpVersion::new(8, 0);
+            settings.entity_style = EntityStyle::Readonly;
+        })
+        .unwrap_err();
+        assert!(error.to_string().contains("needs PHP 8.1"));
+    }
+
+    #[test]
+    fn test_psr4_layout_puts_each_kind_in_its_own_namespace() {
+        let output = "target/test-output/psr4";
+        generate_with_settings(
+            output,
+            "CREATE TABLE tasks (id INT PRIMARY KEY, state ENUM('to-do', 'done') NOT NULL DEFAULT 'to-do');",
+            "-- name: GetTask :one
+SELECT * FROM tasks WHERE id = :id;
+
+-- name: ListStates :many
+SELECT state FROM tasks;",
+            |settings| settings.layout = OutputLayout::Psr4,
+        );
+
+        let state = fs::read_to_string(Path::new(output).join("Enum/TasksState.php")).unwrap();
+        assert!(state.contains("namespace App\\Db\\Enum;"));
+
+        let entity = fs::read_to_string(Path::new(output).join("Entity/Tasks.php")).unwrap();
+        assert!(entity.contains("namespace App\\Db\\Entity;"));
+        assert!(entity.contains("use App\\Db\\Enum\\TasksState;"));
+        assert!(entity.contains("public const DEFAULT_STATE = TasksState::ToDo;"));
+
+        let row = fs::read_to_string(Path::new(output).join("Row/ListStatesRow.php")).unwrap();
+        assert!(row.contains("namespace App\\Db\\Row;"));
+        assert!(row.contains("use App\\Db\\Enum\\TasksState;"));
+
+        let queries = fs::read_to_string(Path::new(output).join("Queries/Queries.php")).unwrap();
+        assert!(queries.contains("namespace App\\Db\\Queries;"));
+        assert!(queries.contains(
+            "use App\\Db\\Entity\\Tasks;
+use App\\Db\\Row\\ListStatesRow;
+use PDO;"
+        ));
+        assert!(queries.contains("public function getTask(int $id): ?Tasks"));
+    }
+
+    #[test]
+    fn test_composer_autoload_is_registered() {
+        let base = Path::new("target/test-output/composer");
+        let _ = fs::remove_dir_all(base);
+        fs::create_dir_all(base).unwrap();
+        let composer = base.join("composer.json");
+        fs::write(
+            &composer,
+            r#"{"name": "acme/app", "require": {"php": "^8.2"}, "autoload": {"psr-4": {"App\\": "src/"}}}"#,
+        )
+        .unwrap();
+
+        let generate = || {
+            generate_with_settings(
+                "target/test-output/composer/src/Db",
+                "CREATE TABLE posts (id INT PRIMARY KEY);",
+                "-- name: GetPost :one
+SELECT * FROM posts WHERE id = :id;",
+                |settings| settings.composer = Some(composer.to_string_lossy().to_string()),
+            )
+        };
+        generate();
+
+        let content = fs::read_to_string(&composer).unwrap();
+        assert_eq!(
+            content,
+            r#"{
+    "name": "acme/app",
+    "require": {
+        "php": "^8.2"
+    },
+    "autoload": {
+        "psr-4": {
+            "App\\": "src/",
+            "App\\Db\\": "src/Db/"
+        }
+    }
+}
+"#
+        );
+
+        generate();
+        assert_eq!(fs::read_to_string(&composer).unwrap(), content);
+    }
+}